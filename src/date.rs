@@ -0,0 +1,475 @@
+use chrono::{Datelike, Duration, Month, NaiveDate, Weekday};
+use num_traits::cast::FromPrimitive;
+
+/// A month of a specific year, like November 2022.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MonthOfYear {
+    year: i32,
+
+    /// 1 to 12
+    month: u32,
+}
+
+impl MonthOfYear {
+    pub fn new(year: i32, month: u32) -> Option<Self> {
+        NaiveDate::from_ymd_opt(year, month, 1)?;
+        Some(Self { year, month })
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    pub fn month(&self) -> u32 {
+        self.month
+    }
+
+    /// The quarter of the year, 1 to 4.
+    pub fn quarter(&self) -> u8 {
+        ((self.month - 1) / 3 + 1) as u8
+    }
+
+    /// Every day of the month, in order.
+    pub fn days(&self) -> impl Iterator<Item = NaiveDate> {
+        let first = self.first_day();
+        first
+            .iter_days()
+            .take_while(move |d| d.month() == first.month())
+    }
+
+    /// The days of the month falling on `weekday`.
+    pub fn weekday_dates(&self, weekday: Weekday) -> Vec<u8> {
+        self.days()
+            .filter(|d| d.weekday() == weekday)
+            .map(|d| d.day() as u8)
+            .collect()
+    }
+
+    /// The number of days of the month that are neither on a `weekend` day
+    /// nor among the `holidays` (days of the month).
+    pub fn business_days(&self, weekend: &[Weekday], holidays: &[u8]) -> u8 {
+        self.days()
+            .filter(|d| !weekend.contains(&d.weekday()))
+            .filter(|d| !holidays.contains(&(d.day() as u8)))
+            .count() as u8
+    }
+
+    /// The 0-based week row holding `day` in a grid of weeks starting on
+    /// `fday`, if the month has that day.
+    pub fn week_index_of(&self, day: u8, fday: Weekday) -> Option<u8> {
+        let date = self.first_day().with_day(day as u32)?;
+        let offset = (7 + self.first_day().weekday().num_days_from_sunday()
+            - fday.num_days_from_sunday())
+            % 7;
+        Some(((offset + date.day() - 1) / 7) as u8)
+    }
+
+    /// The number of Saturday-Sunday pairs with both days in the month.
+    pub fn full_weekends(&self) -> u8 {
+        self.days()
+            .filter(|d| d.weekday() == Weekday::Sat)
+            .filter(|d| d.succ_opt().map(|s| s.month()) == Some(self.month))
+            .count() as u8
+    }
+
+    /// The month as braille, dots for its days in a grid of six weeks from
+    /// Sunday: each character holds two weekdays of four weeks, so the grid
+    /// takes two lines of four characters.
+    pub fn to_braille(&self) -> String {
+        // dot bits of a braille cell by row, left column then right
+        const DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+        let start = self.first_day().week(Weekday::Sun).first_day();
+        let in_month = |week: usize, weekday: usize| {
+            let date = start + Duration::days((7 * week + weekday) as i64);
+            weekday < 7 && date.month() == self.month
+        };
+        (0..2)
+            .map(|line| {
+                (0..4)
+                    .map(|cell| {
+                        let mut bits = 0;
+                        for (row, dots) in DOTS.iter().enumerate() {
+                            for (col, dot) in dots.iter().enumerate() {
+                                if in_month(4 * line + row, 2 * cell + col) {
+                                    bits |= dot;
+                                }
+                            }
+                        }
+                        char::from_u32(0x2800 + bits).unwrap()
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// The first day of the month.
+    pub(crate) fn first_day(&self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(self.year, self.month, 1).unwrap()
+    }
+
+    /// The number of months since January of year 0.
+    pub fn absolute_index(&self) -> i64 {
+        self.year as i64 * 12 + (self.month as i64 - 1)
+    }
+
+    /// Inverse of `absolute_index`, or `None` past the months chrono can
+    /// represent.
+    pub fn from_absolute_index(index: i64) -> Option<Self> {
+        let year = i32::try_from(index.div_euclid(12)).ok()?;
+        Self::new(year, index.rem_euclid(12) as u32 + 1)
+    }
+
+    /// The month and day `offset` days after `epoch`, or before it if
+    /// negative.
+    pub fn at_day_offset(epoch: (i32, u32, u32), offset: i64) -> Option<(Self, u8)> {
+        let epoch = NaiveDate::from_ymd_opt(epoch.0, epoch.1, epoch.2)?;
+        let date = epoch.checked_add_signed(Duration::days(offset))?;
+        Some((
+            Self {
+                year: date.year(),
+                month: date.month(),
+            },
+            date.day() as u8,
+        ))
+    }
+
+    /// The previous month, unless this is the first chrono can represent.
+    pub fn pred(&self) -> Option<Self> {
+        Self::from_absolute_index(self.absolute_index() - 1)
+    }
+
+    /// The next month, unless this is the last chrono can represent.
+    pub fn succ(&self) -> Option<Self> {
+        Self::from_absolute_index(self.absolute_index() + 1)
+    }
+}
+
+/// A calendar year, within the range chrono can represent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Year {
+    year: i32,
+}
+
+impl Year {
+    pub fn new(year: i32) -> Option<Self> {
+        NaiveDate::from_ymd_opt(year, 1, 1)?;
+        NaiveDate::from_ymd_opt(year, 12, 31)?;
+        Some(Self { year })
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    pub fn is_leap_year(&self) -> bool {
+        NaiveDate::from_ymd_opt(self.year, 2, 29).is_some()
+    }
+
+    /// 365, or 366 in a leap year.
+    pub fn num_days(&self) -> u16 {
+        if self.is_leap_year() {
+            366
+        } else {
+            365
+        }
+    }
+
+    /// The weekday of a day in this year, if the day exists.
+    pub fn weekday_of(&self, month: u32, day: u32) -> Option<Weekday> {
+        NaiveDate::from_ymd_opt(self.year, month, day).map(|d| d.weekday())
+    }
+
+    /// The weekday of December 31.
+    pub fn last_weekday(&self) -> Weekday {
+        self.weekday_of(12, 31).unwrap()
+    }
+
+    /// The number of ISO weeks of the year, 53 if it starts on a Thursday,
+    /// or on a Wednesday in a leap year, otherwise 52.
+    pub fn iso_weeks(&self) -> u8 {
+        match self.weekday_of(1, 1).unwrap() {
+            Weekday::Thu => 53,
+            Weekday::Wed if self.is_leap_year() => 53,
+            _ => 52,
+        }
+    }
+
+    /// Whether the two years share every date's weekday, so that a printed
+    /// calendar of one serves for the other: they start on the same weekday
+    /// and are both leap or both common years.
+    pub fn layout_equivalent(&self, other: &Year) -> bool {
+        self.signature() == other.signature()
+    }
+
+    /// The weekday of January 1 and whether the year is a leap year, which
+    /// together fix the weekday of every date.
+    pub fn signature(&self) -> (Weekday, bool) {
+        (self.weekday_of(1, 1).unwrap(), self.is_leap_year())
+    }
+
+    /// The weekday each month starts on, from January.
+    pub fn month_start_weekdays(&self) -> [Weekday; 12] {
+        [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12].map(|m| self.weekday_of(m, 1).unwrap())
+    }
+
+    /// A table of the weekday each month starts on, one line per month
+    /// like "January   Sat".
+    pub fn first_day_table(&self) -> String {
+        (1..=12)
+            .zip(self.month_start_weekdays())
+            .map(|(m, weekday)| {
+                let month = Month::from_u32(m).unwrap().name();
+                format!("{:<9} {}", month, weekday)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Every date of the year falling on `weekday`, as months and days.
+    pub fn all_weekday_dates(&self, weekday: Weekday) -> Vec<(Month, u8)> {
+        (1..=12)
+            .flat_map(|m| {
+                let month = Month::from_u32(m).unwrap();
+                MonthOfYear::new(self.year, m)
+                    .unwrap()
+                    .weekday_dates(weekday)
+                    .into_iter()
+                    .map(move |d| (month, d))
+            })
+            .collect()
+    }
+
+    /// Months whose 13th falls on a Friday.
+    pub fn friday_13ths(&self) -> Vec<Month> {
+        (1..=12)
+            .filter(|&m| self.weekday_of(m, 13) == Some(Weekday::Fri))
+            .map(|m| Month::from_u32(m).unwrap())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn absolute_index_succ() {
+        let nov = MonthOfYear::new(2022, 11).unwrap();
+        assert_eq!(nov.absolute_index(), 2022 * 12 + 10);
+        assert_eq!(
+            nov.succ().unwrap().absolute_index(),
+            nov.absolute_index() + 1
+        );
+        let dec = MonthOfYear::new(2022, 12).unwrap();
+        assert_eq!(dec.succ(), MonthOfYear::new(2023, 1));
+        assert_eq!(dec.succ().unwrap().pred(), Some(dec));
+        let last = NaiveDate::MAX;
+        let last = MonthOfYear::new(last.year(), last.month()).unwrap();
+        assert_eq!(last.succ(), None);
+        let first = NaiveDate::MIN;
+        let first = MonthOfYear::new(first.year(), first.month()).unwrap();
+        assert_eq!(first.pred(), None);
+    }
+
+    #[test]
+    fn absolute_index_round_trip() {
+        for index in -30..30 {
+            let m = MonthOfYear::from_absolute_index(index).unwrap();
+            assert!((1..=12).contains(&m.month()));
+            assert_eq!(m.absolute_index(), index);
+        }
+        let m = MonthOfYear::new(1970, 1).unwrap();
+        assert_eq!(
+            MonthOfYear::from_absolute_index(m.absolute_index()),
+            Some(m)
+        );
+        assert_eq!(MonthOfYear::from_absolute_index(i64::MAX), None);
+        assert_eq!(MonthOfYear::from_absolute_index(i64::MIN), None);
+    }
+
+    #[test]
+    fn month_at_day_offset() {
+        let dec = MonthOfYear::new(2022, 12).unwrap();
+        assert_eq!(
+            MonthOfYear::at_day_offset((2022, 11, 1), 45),
+            Some((dec, 16))
+        );
+        let oct = MonthOfYear::new(2022, 10).unwrap();
+        assert_eq!(
+            MonthOfYear::at_day_offset((2022, 11, 1), -1),
+            Some((oct, 31))
+        );
+        assert_eq!(
+            MonthOfYear::at_day_offset((2022, 11, 1), -31),
+            Some((oct, 1))
+        );
+        assert_eq!(MonthOfYear::at_day_offset((2022, 11, 31), 0), None);
+    }
+
+    #[test]
+    fn month_quarter() {
+        assert_eq!(MonthOfYear::new(2022, 2).unwrap().quarter(), 1);
+        assert_eq!(MonthOfYear::new(2022, 7).unwrap().quarter(), 3);
+        assert_eq!(MonthOfYear::new(2022, 12).unwrap().quarter(), 4);
+    }
+
+    #[test]
+    fn month_weekday_dates() {
+        let nov = MonthOfYear::new(2022, 11).unwrap();
+        assert_eq!(nov.weekday_dates(Weekday::Fri), [4, 11, 18, 25]);
+        assert_eq!(nov.weekday_dates(Weekday::Tue), [1, 8, 15, 22, 29]);
+    }
+
+    #[test]
+    fn month_business_days() {
+        let nov = MonthOfYear::new(2022, 11).unwrap();
+        let weekend = [Weekday::Sat, Weekday::Sun];
+        assert_eq!(nov.business_days(&weekend, &[]), 22);
+        assert_eq!(nov.business_days(&weekend, &[24]), 21);
+        // holidays on weekends do not count twice
+        assert_eq!(nov.business_days(&weekend, &[24, 26]), 21);
+        assert_eq!(nov.business_days(&[Weekday::Fri, Weekday::Sat], &[]), 22);
+        assert_eq!(nov.business_days(&[], &[]), 30);
+    }
+
+    #[test]
+    fn month_week_index_of() {
+        let nov = MonthOfYear::new(2022, 11).unwrap();
+        assert_eq!(nov.week_index_of(1, Weekday::Sun), Some(0));
+        assert_eq!(nov.week_index_of(5, Weekday::Sun), Some(0));
+        assert_eq!(nov.week_index_of(6, Weekday::Sun), Some(1));
+        assert_eq!(nov.week_index_of(30, Weekday::Sun), Some(4));
+        assert_eq!(nov.week_index_of(7, Weekday::Mon), Some(1));
+        assert_eq!(nov.week_index_of(31, Weekday::Sun), None);
+        let may = MonthOfYear::new(2021, 5).unwrap();
+        assert_eq!(may.week_index_of(31, Weekday::Sun), Some(5));
+    }
+
+    #[test]
+    fn month_full_weekends() {
+        assert_eq!(MonthOfYear::new(2022, 11).unwrap().full_weekends(), 4);
+        // ends on Saturday the 31st
+        assert_eq!(MonthOfYear::new(2022, 12).unwrap().full_weekends(), 4);
+        // starts on Sunday the 1st
+        assert_eq!(MonthOfYear::new(2023, 1).unwrap().full_weekends(), 4);
+        assert_eq!(MonthOfYear::new(2022, 10).unwrap().full_weekends(), 5);
+    }
+
+    #[test]
+    fn month_to_braille() {
+        let braille = MonthOfYear::new(2022, 11).unwrap().to_braille();
+        let lines: Vec<_> = braille.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|l| l.chars().count() == 4));
+        // the first week lacks Sunday and Monday, the top dots of "⣶"
+        assert_eq!(lines[0], "⣶⣿⣿⡇");
+        assert_eq!(lines[1], "⠉⠉⠀⠀");
+    }
+
+    #[test]
+    fn year_last_weekday() {
+        assert_eq!(Year::new(2022).unwrap().last_weekday(), Weekday::Sat);
+        assert_eq!(Year::new(2020).unwrap().last_weekday(), Weekday::Thu);
+    }
+
+    #[test]
+    fn year_friday_13ths() {
+        assert_eq!(Year::new(2022).unwrap().friday_13ths(), [Month::May]);
+        assert_eq!(
+            Year::new(2015).unwrap().friday_13ths(),
+            [Month::February, Month::March, Month::November]
+        );
+    }
+
+    #[test]
+    fn year_layout_equivalent() {
+        let year = |y| Year::new(y).unwrap();
+        assert!(year(2022).layout_equivalent(&year(2033)));
+        assert!(!year(2022).layout_equivalent(&year(2023)));
+        assert!(year(2012).layout_equivalent(&year(2040)));
+        // 2016 also starts on a Friday, but is a leap year
+        assert!(!year(2016).layout_equivalent(&year(2021)));
+    }
+
+    #[test]
+    fn year_signature() {
+        assert_eq!(Year::new(2022).unwrap().signature(), (Weekday::Sat, false));
+        assert_eq!(Year::new(2020).unwrap().signature(), (Weekday::Wed, true));
+        assert_eq!(
+            Year::new(2022).unwrap().signature(),
+            Year::new(2033).unwrap().signature()
+        );
+    }
+
+    #[test]
+    fn year_first_day_table() {
+        let table = Year::new(2022).unwrap().first_day_table();
+        let lines: Vec<_> = table.lines().collect();
+        assert_eq!(lines.len(), 12);
+        assert_eq!(lines[0], "January   Sat");
+        assert_eq!(lines[8], "September Thu");
+        let weekdays: Vec<_> = lines.iter().map(|l| &l[10..]).collect();
+        assert_eq!(
+            weekdays,
+            ["Sat", "Tue", "Tue", "Fri", "Sun", "Wed", "Fri", "Mon", "Thu", "Sat", "Tue", "Thu"]
+        );
+    }
+
+    #[test]
+    fn year_month_start_weekdays() {
+        use Weekday::*;
+        assert_eq!(
+            Year::new(2022).unwrap().month_start_weekdays(),
+            [Sat, Tue, Tue, Fri, Sun, Wed, Fri, Mon, Thu, Sat, Tue, Thu]
+        );
+        // a leap year shifts the months from March
+        assert_eq!(
+            Year::new(2024).unwrap().month_start_weekdays()[1..4],
+            [Thu, Fri, Mon]
+        );
+    }
+
+    #[test]
+    fn year_iso_weeks() {
+        assert_eq!(Year::new(2020).unwrap().iso_weeks(), 53);
+        assert_eq!(Year::new(2021).unwrap().iso_weeks(), 52);
+        assert_eq!(Year::new(2026).unwrap().iso_weeks(), 53);
+        // starts on a Wednesday, but a common year
+        assert_eq!(Year::new(2025).unwrap().iso_weeks(), 52);
+        for y in 1990..2040 {
+            let dec28 = NaiveDate::from_ymd_opt(y, 12, 28).unwrap();
+            let weeks = dec28.iso_week().week() as u8;
+            assert_eq!(Year::new(y).unwrap().iso_weeks(), weeks, "{}", y);
+        }
+    }
+
+    #[test]
+    fn year_all_weekday_dates() {
+        let mondays = Year::new(2022).unwrap().all_weekday_dates(Weekday::Mon);
+        assert_eq!(mondays.len(), 52);
+        assert_eq!(
+            mondays[..6],
+            [
+                (Month::January, 3),
+                (Month::January, 10),
+                (Month::January, 17),
+                (Month::January, 24),
+                (Month::January, 31),
+                (Month::February, 7),
+            ]
+        );
+        // the year starts and ends on a Saturday
+        let saturdays = Year::new(2022).unwrap().all_weekday_dates(Weekday::Sat);
+        assert_eq!(saturdays.len(), 53);
+        assert_eq!(saturdays.last(), Some(&(Month::December, 31)));
+    }
+
+    #[test]
+    fn year_num_days() {
+        assert_eq!(Year::new(2020).unwrap().num_days(), 366);
+        assert_eq!(Year::new(2021).unwrap().num_days(), 365);
+        assert_eq!(Year::new(1900).unwrap().num_days(), 365);
+        assert_eq!(Year::new(2000).unwrap().num_days(), 366);
+    }
+}
@@ -1,77 +1,517 @@
-use chrono::{Datelike, Month, Months, NaiveDate, Weekday};
+use chrono::{Datelike, Local, Month, Months, NaiveDate, Weekday};
 use colored::Colorize;
 use itertools::Itertools;
 use num_traits::cast::FromPrimitive;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+mod date;
+
+pub use date::{MonthOfYear, Year};
 
 const MONTH_WIDTH: usize = 3 * 7;
 const DAY_ROWS: usize = 6;
 const MONTH_ROWS: usize = DAY_ROWS + 2;
 
+/// A value attached to days, e.g. the number of events on each day.
+pub trait DayValue {
+    fn value(&self, ymd: (i32, u32, u32)) -> Option<i64>;
+}
+
+impl<F> DayValue for F
+where
+    F: Fn((i32, u32, u32)) -> Option<i64>,
+{
+    fn value(&self, ymd: (i32, u32, u32)) -> Option<i64> {
+        self(ymd)
+    }
+}
+
+/// A text effect for cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    Plain,
+    Dimmed,
+    Italic,
+    Bold,
+    Underline,
+    Reversed,
+}
+
+impl Effect {
+    fn apply(&self, cell: String) -> String {
+        match self {
+            Effect::Plain => cell,
+            Effect::Dimmed => cell.dimmed().to_string(),
+            Effect::Italic => cell.italic().to_string(),
+            Effect::Bold => cell.bold().to_string(),
+            Effect::Underline => cell.underline().to_string(),
+            Effect::Reversed => cell.reversed().to_string(),
+        }
+    }
+}
+
+impl std::str::FromStr for Effect {
+    type Err = String;
+
+    /// Read an effect named like "dim" or "reverse".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(Effect::Plain),
+            "dim" => Ok(Effect::Dimmed),
+            "italic" => Ok(Effect::Italic),
+            "bold" => Ok(Effect::Bold),
+            "underline" => Ok(Effect::Underline),
+            "reverse" => Ok(Effect::Reversed),
+            _ => Err(format!(
+                "expected plain, dim, italic, bold, underline or reverse, got `{}`",
+                s
+            )),
+        }
+    }
+}
+
+/// Effects of a highlighted range of days.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeHighlightStyle {
+    /// the first and last days, both on a single-day range
+    pub endpoints: Effect,
+
+    /// the days between them
+    pub interior: Effect,
+}
+
+impl Default for RangeHighlightStyle {
+    fn default() -> Self {
+        Self {
+            endpoints: Effect::Bold,
+            interior: Effect::Reversed,
+        }
+    }
+}
+
+/// Side of its cell a day number keeps to, " 1" or "1 ".
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DayAlign {
+    Left,
+    #[default]
+    Right,
+}
+
+/// Boxes of a printable planner, each `width` columns by `height` lines
+/// inside, with the day number in its top-left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlannerStyle {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Default for PlannerStyle {
+    fn default() -> Self {
+        Self {
+            width: 10,
+            height: 4,
+        }
+    }
+}
+
+/// A way of numbering weekdays, for reading a first weekday given as a
+/// number.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WeekdayBase {
+    /// Sunday = 0, Monday = 1, ..., Saturday = 6
+    #[default]
+    Sunday0,
+
+    /// Monday = 0, ..., Sunday = 6
+    Monday0,
+
+    /// Monday = 1, ..., Sunday = 7, as ISO 8601
+    Monday1,
+}
+
+impl WeekdayBase {
+    /// The weekday numbered `n`, as `Calendar::new` takes it (Sunday = 0).
+    pub fn first_day(self, n: u8) -> Option<u8> {
+        match self {
+            Self::Sunday0 if n <= 6 => Some(n),
+            Self::Monday0 if n <= 6 => Some((n + 1) % 7),
+            Self::Monday1 if (1..=7).contains(&n) => Some(n % 7),
+            _ => None,
+        }
+    }
+}
+
+/// Letter case of weekday names, "Su" as given, "SU" or "su".
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WeekdayCase {
+    #[default]
+    Title,
+    Upper,
+    Lower,
+}
+
+/// Text of a day cell from its year, month, day and weekday.
+type DayText = dyn Fn(i32, u32, u32, Weekday) -> String;
+
+/// Whether to highlight a day, from its year, month, day and weekday.
+type DayPredicate = dyn Fn(i32, u32, u32, Weekday) -> bool;
+
+/// Rendering options shared by every month block.
+#[derive(Clone)]
+struct Style {
+    /// list the weeks of a month bottom-up
+    flip: bool,
+
+    /// per-day values for value-driven styling
+    values: Option<Rc<dyn DayValue>>,
+
+    /// embolden the day with the largest value in each month
+    mark_max: bool,
+
+    /// shade every other week row of a month
+    zebra_weeks: bool,
+
+    /// number base of day numbers
+    radix: u32,
+
+    /// append the month number to headers, like "November 2022 (11)"
+    month_number: bool,
+
+    /// mark the highlighted day with a character instead of reverse video
+    today_prefix: Option<char>,
+
+    /// paint Saturdays and Sundays red
+    color_weekends: bool,
+
+    /// draw days as "[ ]", or "[x]" where the day value is positive
+    checkbox: bool,
+
+    /// weekday abbreviations from Sunday, instead of "Su", "Mo", ...
+    weekday_names: Option<[String; 7]>,
+
+    /// draw a line between week rows
+    week_dividers: bool,
+
+    /// custom text of day cells, cut to the cell width
+    day_text: Option<Rc<DayText>>,
+
+    /// blank lines between the weekday row and the first week
+    header_gap: usize,
+
+    /// look of days shown from the previous month
+    prev_overflow: Effect,
+
+    /// look of days shown from the next month
+    next_overflow: Effect,
+
+    /// letter case of weekday names
+    weekday_case: WeekdayCase,
+
+    /// write every day as a dot, except this one
+    minimal: Option<NaiveDate>,
+
+    /// first and last days of a highlighted range
+    range: Option<(NaiveDate, NaiveDate)>,
+
+    /// effects of the highlighted range
+    range_style: RangeHighlightStyle,
+
+    /// side of the cell day numbers keep to
+    day_align: DayAlign,
+
+    /// days to highlight and the effect to highlight them with
+    highlight_if: Option<(Rc<DayPredicate>, Effect)>,
+
+    /// effects of single days
+    day_effects: HashMap<NaiveDate, Effect>,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            flip: false,
+            values: None,
+            mark_max: false,
+            zebra_weeks: false,
+            radix: 10,
+            month_number: false,
+            today_prefix: None,
+            color_weekends: true,
+            checkbox: false,
+            weekday_names: None,
+            week_dividers: false,
+            day_text: None,
+            header_gap: 0,
+            prev_overflow: Effect::Dimmed,
+            next_overflow: Effect::Dimmed,
+            weekday_case: WeekdayCase::default(),
+            minimal: None,
+            range: None,
+            range_style: RangeHighlightStyle::default(),
+            day_align: DayAlign::default(),
+            highlight_if: None,
+            day_effects: HashMap::new(),
+        }
+    }
+}
+
+impl Style {
+    /// Width of a day cell, without its gap, wide enough for every weekday
+    /// abbreviation.
+    fn cell_width(&self) -> usize {
+        let day = if self.checkbox { 3 } else { 2 };
+        let name = self
+            .weekday_names
+            .iter()
+            .flatten()
+            .map(|n| n.chars().count())
+            .max()
+            .unwrap_or(0);
+        day.max(name)
+    }
+
+    /// Width of a month block: seven cells with their gaps.
+    fn month_width(&self) -> usize {
+        7 * (self.cell_width() + 1)
+    }
+
+    /// The number of lines taken by `weeks` week rows.
+    fn day_rows(&self, weeks: usize) -> usize {
+        if self.week_dividers {
+            2 * weeks - 1
+        } else {
+            weeks
+        }
+    }
+
+    /// The number of lines above the first week of a month block.
+    fn header_rows(&self) -> usize {
+        2 + self.header_gap
+    }
+
+    /// The number of lines of a month block with all its padding rows.
+    fn month_rows(&self) -> usize {
+        MONTH_ROWS + self.header_gap + self.day_rows(DAY_ROWS) - DAY_ROWS
+    }
+
+    /// Lines with `divider` between each two of them, if week dividers are on.
+    fn divide(&self, lines: Vec<String>, divider: &str) -> Vec<String> {
+        if !self.week_dividers {
+            return lines;
+        }
+        let mut divided = Vec::with_capacity(2 * lines.len());
+        for (i, line) in lines.into_iter().enumerate() {
+            if i > 0 {
+                divided.push(divider.to_string());
+            }
+            divided.push(line);
+        }
+        divided
+    }
+
+    /// Whether the day value of `date` marks it done.
+    fn is_done(&self, date: NaiveDate) -> bool {
+        let value = self
+            .values
+            .as_ref()
+            .and_then(|v| v.value((date.year(), date.month(), date.day())));
+        matches!(value, Some(v) if v > 0)
+    }
+
+    /// Whether `date` holds the largest value of its month, earliest on ties.
+    fn is_max(&self, date: NaiveDate) -> bool {
+        let values = match &self.values {
+            Some(values) if self.mark_max => values,
+            _ => return false,
+        };
+        date.with_day(1)
+            .unwrap()
+            .iter_days()
+            .take_while(|d| d.month() == date.month())
+            .filter_map(|d| Some((values.value((d.year(), d.month(), d.day()))?, d)))
+            .max_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)))
+            .map(|(_, d)| d)
+            == Some(date)
+    }
+}
+
 /// A line like "    November 2022    ".
-fn month_year_line(date: NaiveDate, full_year: bool) -> String {
+fn month_year_line(date: NaiveDate, full_year: bool, style: &Style) -> String {
     let month = Month::from_u32(date.month()).unwrap();
-    let header = if full_year {
+    let mut header = if full_year {
         month.name().to_string()
     } else {
         format!("{} {}", month.name(), date.year())
     };
-    format!("{:^1$}", header, MONTH_WIDTH)
+    if style.month_number {
+        header = format!("{} ({})", header, date.month());
+    }
+    format!("{:^1$}", header, style.month_width())
+}
+
+/// Digits drawn with blocks, three columns by five rows.
+const BLOCK_DIGITS: [[&str; 5]; 10] = [
+    ["███", "█ █", "█ █", "█ █", "███"],
+    [" █ ", "██ ", " █ ", " █ ", "███"],
+    ["███", "  █", "███", "█  ", "███"],
+    ["███", "  █", "███", "  █", "███"],
+    ["█ █", "█ █", "███", "  █", "  █"],
+    ["███", "█  ", "███", "  █", "███"],
+    ["███", "█  ", "███", "█ █", "███"],
+    ["███", "  █", "  █", "  █", "  █"],
+    ["███", "█ █", "███", "█ █", "███"],
+    ["███", "█ █", "███", "  █", "███"],
+];
+
+/// A number like "2022" or "-44" in block digits, five lines a column apart.
+fn block_digits(number: &str) -> Vec<String> {
+    (0..5)
+        .map(|row| {
+            number
+                .chars()
+                .map(|c| match c.to_digit(10) {
+                    Some(d) => BLOCK_DIGITS[d as usize][row],
+                    None if row == 2 => "███",
+                    None => "   ",
+                })
+                .join(" ")
+        })
+        .collect()
 }
 
 /// A cell like "Su" or "Mo".
-fn weekday_cell(weekday: Weekday) -> String {
+fn weekday_cell(weekday: Weekday, style: &Style) -> String {
+    let cell = match &style.weekday_names {
+        Some(names) => &names[weekday.num_days_from_sunday() as usize],
+        None => match weekday {
+            Weekday::Mon => "Mo",
+            Weekday::Tue => "Tu",
+            Weekday::Wed => "We",
+            Weekday::Thu => "Th",
+            Weekday::Fri => "Fr",
+            Weekday::Sat => "Sa",
+            Weekday::Sun => "Su",
+        },
+    };
+    let cell = match style.weekday_case {
+        WeekdayCase::Title => cell.to_string(),
+        WeekdayCase::Upper => cell.to_uppercase(),
+        WeekdayCase::Lower => cell.to_lowercase(),
+    };
+    let cell = format!("{:>1$}", cell, style.cell_width());
     match weekday {
-        Weekday::Mon => "Mo".to_string(),
-        Weekday::Tue => "Tu".to_string(),
-        Weekday::Wed => "We".to_string(),
-        Weekday::Thu => "Th".to_string(),
-        Weekday::Fri => "Fr".to_string(),
-        Weekday::Sat => "Sa".red().to_string(),
-        Weekday::Sun => "Su".red().to_string(),
+        Weekday::Sat | Weekday::Sun if style.color_weekends => cell.red().to_string(),
+        _ => cell,
     }
 }
 
 /// A line like "Su Mo Tu We Th Fr Sa ", starting at `start`.
-fn weekday_line(start: Weekday) -> String {
+fn weekday_line(start: Weekday, style: &Style) -> String {
     itertools::iterate(start, Weekday::succ)
         .take(7)
-        .map(|w| format!("{} ", weekday_cell(w)))
+        .map(|w| format!("{} ", weekday_cell(w, style)))
         .join("")
 }
 
-/// A cell like " 1" or "31".
-fn day_cell(date: NaiveDate) -> String {
-    let cell = format!("{:>2}", date.day());
+/// A cell like " 1" or "31", or like "01" or "1F" in other radices.
+fn day_cell(date: NaiveDate, style: &Style) -> String {
+    let radix = style.radix;
+    let cell = if matches!(style.minimal, Some(label) if label != date) {
+        "·".to_string()
+    } else if let Some(text) = &style.day_text {
+        let text = text(date.year(), date.month(), date.day(), date.weekday());
+        text.chars().take(style.cell_width()).collect()
+    } else if style.checkbox {
+        let mark = if style.is_done(date) { 'x' } else { ' ' };
+        format!("[{}]", mark)
+    } else if radix == 10 {
+        date.day().to_string()
+    } else {
+        let digit = |n| char::from_digit(n, radix).unwrap().to_ascii_uppercase();
+        format!("{}{}", digit(date.day() / radix), digit(date.day() % radix))
+    };
+    let cell = match style.day_align {
+        DayAlign::Left => format!("{:<1$}", cell, style.cell_width()),
+        DayAlign::Right => format!("{:>1$}", cell, style.cell_width()),
+    };
     match date.weekday() {
-        Weekday::Sat | Weekday::Sun => cell.red().to_string(),
+        Weekday::Sat | Weekday::Sun if style.color_weekends => cell.red().to_string(),
         _ => cell,
     }
 }
 
 /// A line like " 8  9 10 11 12 13 14 ".
 /// Current month must be provided to determine which days to show.
+/// A shaded line carries a background on every cell and gap.
 fn day_line(
     date: NaiveDate,
     start: Weekday,
     cur_month: u32,
     hlight: NaiveDate,
     hint: bool,
+    style: &Style,
+    shaded: bool,
 ) -> String {
+    let shade = |s: String| {
+        if shaded {
+            s.on_bright_black().to_string()
+        } else {
+            s
+        }
+    };
     date.week(start)
         .first_day()
         .iter_days()
         .take(7)
         .map(|d| {
-            if d.month() == cur_month {
-                if d == hlight {
-                    format!("{} ", day_cell(d).reversed())
-                } else {
-                    format!("{} ", day_cell(d))
+            let in_month = d.month() == cur_month;
+            let cell = if in_month {
+                let mut cell = day_cell(d, style);
+                if let Some((first, last)) = style.range {
+                    let effects = &style.range_style;
+                    if first < d && d < last || first == last && d == first {
+                        cell = effects.interior.apply(cell);
+                    }
+                    if d == first || d == last {
+                        cell = effects.endpoints.apply(cell);
+                    }
+                }
+                if let Some(effect) = style.day_effects.get(&d) {
+                    cell = effect.apply(cell);
                 }
-            } else if hint {
-                format!("{} ", day_cell(d).dimmed())
+                if let Some((predicate, effect)) = &style.highlight_if {
+                    if predicate(d.year(), d.month(), d.day(), d.weekday()) {
+                        cell = effect.apply(cell);
+                    }
+                }
+                if style.is_max(d) {
+                    cell = cell.bold().to_string();
+                }
+                if d == hlight && style.today_prefix.is_none() {
+                    cell = cell.reversed().to_string();
+                }
+                cell
+            } else if hint && !style.checkbox {
+                // a box of another month would read as one of this month
+                // days from the previous month are its last, the next its first
+                let effect = if d.day() > 15 {
+                    style.prev_overflow
+                } else {
+                    style.next_overflow
+                };
+                effect.apply(day_cell(d, style))
             } else {
-                "   ".to_string()
+                " ".repeat(style.cell_width())
+            };
+            // with a prefix the gap leads the cell, so the mark can take it
+            match style.today_prefix {
+                Some(prefix) if in_month && d == hlight => {
+                    format!("{}{}", shade(prefix.to_string()), shade(cell))
+                }
+                Some(_) => format!("{}{}", shade(" ".to_string()), shade(cell)),
+                None => format!("{}{}", shade(cell), shade(" ".to_string())),
             }
         })
         .join("")
@@ -83,12 +523,85 @@ fn day_lines(
     start: Weekday,
     hlight: NaiveDate,
     hint: bool,
+    style: &Style,
 ) -> impl Iterator<Item = String> {
-    date.with_day(1)
-        .unwrap()
+    let first = date.with_day(1).unwrap();
+    let mut lines = first
+        .iter_weeks()
+        .take(DAY_ROWS)
+        .enumerate()
+        .map(|(i, d)| {
+            let shaded = style.zebra_weeks && i % 2 == 1;
+            day_line(d, start, date.month(), hlight, hint, style, shaded)
+        })
+        .collect_vec();
+    if style.flip {
+        // only the weeks of this month swap places, padding stays below
+        lines[..week_rows(date, start)].reverse();
+    }
+    // the gap sits on the side of the cells it does in the week rows
+    let rule = "─".repeat(style.month_width() - 1);
+    let divider = if style.today_prefix.is_some() {
+        format!(" {}", rule)
+    } else {
+        format!("{} ", rule)
+    };
+    style.divide(lines, &divider).into_iter()
+}
+
+/// A month as a grid of boxes left blank for writing, numbered in their
+/// corners, with as many week rows as the month needs.
+fn planner(date: NaiveDate, start: Weekday, planner: PlannerStyle) -> Vec<String> {
+    let (width, height) = (planner.width.max(2), planner.height.max(1));
+    let rule = |left: &str, mid: &str, right: &str| {
+        format!("{}{}{}", left, vec!["─".repeat(width); 7].join(mid), right)
+    };
+    let row = |cells: Vec<String>| {
+        let cells = cells.iter().map(|c| format!("{:<1$}", c, width));
+        format!("│{}│", cells.format("│"))
+    };
+    let month = Month::from_u32(date.month()).unwrap();
+    let title = format!("{} {}", month.name(), date.year());
+    let mut lines = vec![
+        format!("{:^1$}", title, 7 * (width + 1) + 1),
+        rule("┌", "┬", "┐"),
+        row(itertools::iterate(start, |w| w.succ())
+            .take(7)
+            .map(|w| w.to_string()[..2].to_string())
+            .collect()),
+    ];
+    let first = date.with_day(1).unwrap();
+    for week in first.iter_weeks().take(week_rows(date, start)) {
+        lines.push(rule("├", "┼", "┤"));
+        let days = week.week(start).first_day().iter_days().take(7);
+        lines.push(row(days
+            .map(|d| {
+                if d.month() == first.month() {
+                    d.day().to_string()
+                } else {
+                    String::new()
+                }
+            })
+            .collect()));
+        lines.extend(vec![row(vec![String::new(); 7]); height - 1]);
+    }
+    lines.push(rule("└", "┴", "┘"));
+    lines
+}
+
+/// The number of day rows holding at least one day of the month.
+fn week_rows(date: NaiveDate, start: Weekday) -> usize {
+    let first = date.with_day(1).unwrap();
+    let last = first
+        .iter_days()
+        .take_while(|d| d.month() == first.month())
+        .last()
+        .unwrap();
+    first
         .iter_weeks()
         .take(DAY_ROWS)
-        .map(move |d| day_line(d, start, date.month(), hlight, hint))
+        .take_while(|d| d.week(start).first_day() <= last)
+        .count()
 }
 
 /// A full month calendar.
@@ -98,14 +611,456 @@ fn calendar(
     full_year: bool,
     hlight: NaiveDate,
     hint: bool,
+    style: &Style,
 ) -> impl Iterator<Item = String> {
-    std::iter::once(month_year_line(date, full_year))
-        .chain(std::iter::once(weekday_line(start)))
-        .chain(day_lines(date, start, hlight, hint))
+    std::iter::once(month_year_line(date, full_year, style))
+        .chain(std::iter::once(if style.today_prefix.is_some() {
+            // move the trailing gap to the front, as day cells do
+            let line = weekday_line(start, style);
+            format!(" {}", &line[..line.len() - 1])
+        } else {
+            weekday_line(start, style)
+        }))
+        .chain(vec![" ".repeat(style.month_width()); style.header_gap])
+        .chain(day_lines(date, start, hlight, hint, style))
+}
+
+impl MonthOfYear {
+    /// The rendered month: exactly the header, the weekday row and six day
+    /// rows, each `MONTH_WIDTH` columns wide.
+    ///
+    /// ```
+    /// use carender::MonthOfYear;
+    /// use chrono::Weekday;
+    ///
+    /// let lines = MonthOfYear::new(2022, 11).unwrap().render_lines(Weekday::Sun);
+    /// assert_eq!(lines.len(), 8);
+    /// assert_eq!(lines[0], "    November 2022    ");
+    /// ```
+    pub fn render_lines(&self, start: Weekday) -> Vec<String> {
+        let style = Style::default();
+        calendar(
+            self.first_day(),
+            start,
+            false,
+            NaiveDate::MIN,
+            false,
+            &style,
+        )
+        .collect()
+    }
+}
+
+impl Year {
+    /// Each month rendered apart as in the year view, headed by its name
+    /// only, weeks starting on `start`.
+    pub fn month_blocks(&self, start: Weekday) -> [Vec<String>; 12] {
+        let style = Style::default();
+        std::array::from_fn(|i| {
+            let first = NaiveDate::from_ymd_opt(self.year(), i as u32 + 1, 1).unwrap();
+            calendar(first, start, true, NaiveDate::MIN, false, &style).collect()
+        })
+    }
+}
+
+/// The customary first weekday (Sunday = 0, Monday = 1, ...) of a locale
+/// like "en-US", "de" or "en_GB.UTF-8", following CLDR's territory data.
+pub fn locale_first_day(locale: &str) -> u8 {
+    let name = locale.split(['.', '@']).next().unwrap_or("");
+    let mut parts = name.split(['_', '-']);
+    let lang = parts.next().unwrap_or("").to_ascii_lowercase();
+    let region = match parts.next() {
+        Some(region) => region.to_ascii_uppercase(),
+        // the territory a bare language most likely means
+        None => match lang.as_str() {
+            "" | "c" | "posix" | "en" => "US",
+            "ja" => "JP",
+            "ko" => "KR",
+            "he" => "IL",
+            "hi" => "IN",
+            "pt" => "BR",
+            "zh" => "CN",
+            "ar" => "EG",
+            "fa" => "IR",
+            _ => "",
+        }
+        .to_string(),
+    };
+    const SUNDAY: &[&str] = &[
+        "AG", "AS", "BD", "BR", "BS", "BT", "BW", "BZ", "CA", "CN", "CO", "DM", "DO", "ET", "GT",
+        "GU", "HK", "HN", "ID", "IL", "IN", "JM", "JP", "KE", "KH", "KR", "LA", "MH", "MM", "MO",
+        "MT", "MX", "MZ", "NI", "NP", "PA", "PE", "PH", "PK", "PR", "PT", "PY", "SA", "SG", "SV",
+        "TH", "TT", "TW", "UM", "US", "VE", "VI", "WS", "YE", "ZA", "ZW",
+    ];
+    const SATURDAY: &[&str] = &[
+        "AE", "AF", "BH", "DJ", "DZ", "EG", "IQ", "IR", "JO", "KW", "LY", "OM", "QA", "SD", "SY",
+    ];
+    if SUNDAY.contains(&region.as_str()) {
+        0
+    } else if SATURDAY.contains(&region.as_str()) {
+        6
+    } else {
+        1
+    }
+}
+
+/// Weekday abbreviations of a locale like "fr_FR.UTF-8", from Sunday, for
+/// the languages whose customary ones are not the English two letters.
+pub fn locale_weekday_names(locale: &str) -> Option<[&'static str; 7]> {
+    let name = locale.split(['.', '@']).next().unwrap_or("");
+    let lang = name.split(['_', '-']).next().unwrap_or("");
+    match lang.to_ascii_lowercase().as_str() {
+        "de" => Some(["So", "Mo", "Di", "Mi", "Do", "Fr", "Sa"]),
+        "es" => Some(["dom", "lun", "mar", "mié", "jue", "vie", "sáb"]),
+        "fr" => Some(["dim", "lun", "mar", "mer", "jeu", "ven", "sam"]),
+        "it" => Some(["dom", "lun", "mar", "mer", "gio", "ven", "sab"]),
+        "nl" => Some(["zo", "ma", "di", "wo", "do", "vr", "za"]),
+        "pt" => Some(["dom", "seg", "ter", "qua", "qui", "sex", "sáb"]),
+        _ => None,
+    }
+}
+
+/// The weekday header of a month block alone, starting at `fday` (Sunday =
+/// 0, ...), for drawing it apart from the days. `names` are abbreviations
+/// from Sunday, like those of `locale_weekday_names`, or the default two
+/// letters if `None`.
+pub fn weekday_header(fday: u8, names: Option<[&str; 7]>) -> Option<String> {
+    let style = Style {
+        weekday_names: names.map(|names| names.map(String::from)),
+        ..Style::default()
+    };
+    Some(weekday_line(Weekday::from_u8(fday)?.pred(), &style))
+}
+
+/// Parse and validate a date like "2022-11-11".
+pub fn parse_date(s: &str) -> Option<(i32, u32, u32)> {
+    // split from the right, so that a year may carry a sign or five digits
+    let (rest, day) = s.rsplit_once('-')?;
+    let (year, month) = rest.rsplit_once('-')?;
+    let date = NaiveDate::from_ymd_opt(year.parse().ok()?, month.parse().ok()?, day.parse().ok()?)?;
+    Some((date.year(), date.month(), date.day()))
+}
+
+/// Read a date like "2022", "2022-11" or "2022-11-11" from the first line
+/// of `input`, as the year and whichever of month and day it gives, for
+/// taking the date from a pipe.
+pub fn read_date(mut input: impl std::io::BufRead) -> Option<(i32, Option<u32>, Option<u32>)> {
+    let mut line = String::new();
+    input.read_line(&mut line).ok()?;
+    let line = line.trim();
+    match line.split('-').collect_vec()[..] {
+        [year] => Some((Year::new(year.parse().ok()?)?.year(), None, None)),
+        [year, month] => {
+            let month = MonthOfYear::new(year.parse().ok()?, month.parse().ok()?)?;
+            Some((month.year(), Some(month.month()), None))
+        }
+        _ => {
+            let (y, m, d) = parse_date(line)?;
+            Some((y, Some(m), Some(d)))
+        }
+    }
+}
+
+/// Read lines like "2022-11-11" or "2022-11-11,bold" into the effect of
+/// each date, reverse video if none is given. Blank lines are skipped; an
+/// unreadable one fails with its line number.
+pub fn parse_day_effects(text: &str) -> Result<HashMap<(i32, u32, u32), Effect>, String> {
+    let mut effects = HashMap::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (date, effect) = match line.split_once(',') {
+            Some((date, effect)) => (date.trim(), effect.trim()),
+            None => (line, "reverse"),
+        };
+        let ymd =
+            parse_date(date).ok_or_else(|| format!("line {}: invalid date `{}`", i + 1, date))?;
+        let effect = effect
+            .parse()
+            .map_err(|err| format!("line {}: {}", i + 1, err))?;
+        effects.insert(ymd, effect);
+    }
+    Ok(effects)
+}
+
+/// The share of its year a date completes, from 1/365 on January 1 to 1.0
+/// on December 31.
+pub fn year_progress(ymd: (i32, u32, u32)) -> Option<f64> {
+    let date = NaiveDate::from_ymd_opt(ymd.0, ymd.1, ymd.2)?;
+    let year = Year::new(date.year())?;
+    Some(date.ordinal() as f64 / year.num_days() as f64)
+}
+
+/// A line like "2022-11-11 is day 315 of 365, ISO 2022-W45-5".
+pub fn date_info(ymd: (i32, u32, u32)) -> Option<String> {
+    let date = NaiveDate::from_ymd_opt(ymd.0, ymd.1, ymd.2)?;
+    let week = date.iso_week();
+    Some(format!(
+        "{} is day {} of {}, ISO {}-W{:02}-{}",
+        date.format("%Y-%m-%d"),
+        date.ordinal(),
+        Year::new(date.year())?.num_days(),
+        week.year(),
+        week.week(),
+        date.weekday().number_from_monday()
+    ))
+}
+
+/// The lit share of the moon at noon UTC of a date, from 0.0 at new moon
+/// to 1.0 at full moon, taking every lunation to last the mean synodic
+/// month from the new moon of 2000-01-06 18:14 UTC. Off by up to a day or
+/// so, as the real lunations vary.
+pub fn moon_illumination(ymd: (i32, u32, u32)) -> Option<f64> {
+    const SYNODIC_MONTH: f64 = 29.530588853;
+    let date = NaiveDate::from_ymd_opt(ymd.0, ymd.1, ymd.2)?;
+    let epoch = NaiveDate::from_ymd_opt(2000, 1, 6)?;
+    let days = date.signed_duration_since(epoch).num_days() as f64 + 0.5 - 0.76;
+    let age = days.rem_euclid(SYNODIC_MONTH) / SYNODIC_MONTH;
+    Some((1.0 - (2.0 * std::f64::consts::PI * age).cos()) / 2.0)
+}
+
+/// A pattern like "cal-{year}-{month:02}.txt" filled for a month, here to
+/// "cal-2022-01.txt". `{month}` has no padding.
+pub fn fill_pattern(pattern: &str, month: MonthOfYear) -> String {
+    pattern
+        .replace("{year}", &month.year().to_string())
+        .replace("{month:02}", &format!("{:02}", month.month()))
+        .replace("{month}", &month.month().to_string())
+}
+
+/// The first date after `from` falling on `month` and `day`, skipping years
+/// without it (like February 29 in common years).
+pub fn next_date_matching(from: (i32, u32, u32), month: u32, day: u32) -> Option<(i32, u32, u32)> {
+    let from = NaiveDate::from_ymd_opt(from.0, from.1, from.2)?;
+    // leap years are at most eight years apart
+    (from.year()..=from.year().checked_add(8)?)
+        .filter_map(|y| NaiveDate::from_ymd_opt(y, month, day))
+        .find(|&d| d > from)
+        .map(|d| (d.year(), d.month(), d.day()))
+}
+
+/// The date `n` business days after `start`, counting only days that are
+/// neither on a `weekend` day nor among the `holidays`.
+pub fn add_business_days(
+    start: (i32, u32, u32),
+    n: u32,
+    weekend: &[Weekday],
+    holidays: &[(i32, u32, u32)],
+) -> Option<(i32, u32, u32)> {
+    let start = NaiveDate::from_ymd_opt(start.0, start.1, start.2)?;
+    if n > 0 && weekend.iter().unique().count() == 7 {
+        return None;
+    }
+    let mut date = start;
+    for _ in 0..n {
+        date = date.iter_days().skip(1).find(|d| {
+            !weekend.contains(&d.weekday()) && !holidays.contains(&(d.year(), d.month(), d.day()))
+        })?;
+    }
+    Some((date.year(), date.month(), date.day()))
+}
+
+/// The `weekend` day closest to `from`, `from` itself if it is one, the
+/// later one on ties.
+pub fn nearest_weekend(from: (i32, u32, u32), weekend: &[Weekday]) -> Option<(i32, u32, u32)> {
+    let from = NaiveDate::from_ymd_opt(from.0, from.1, from.2)?;
+    (0..7)
+        .flat_map(|n| {
+            let n = chrono::Duration::days(n);
+            [from.checked_add_signed(n), from.checked_sub_signed(n)]
+        })
+        .flatten()
+        .find(|d| weekend.contains(&d.weekday()))
+        .map(|d| (d.year(), d.month(), d.day()))
+}
+
+/// The last date before `from` falling on `month` and `day`.
+pub fn prev_date_matching(from: (i32, u32, u32), month: u32, day: u32) -> Option<(i32, u32, u32)> {
+    let from = NaiveDate::from_ymd_opt(from.0, from.1, from.2)?;
+    (from.year().checked_sub(8)?..=from.year())
+        .rev()
+        .filter_map(|y| NaiveDate::from_ymd_opt(y, month, day))
+        .find(|&d| d < from)
+        .map(|d| (d.year(), d.month(), d.day()))
+}
+
+/// The seven days of the week containing a date, across month boundaries.
+pub fn week_view(ymd: (i32, u32, u32), fday: u8, line_ending: LineEnding) -> Option<String> {
+    weeks_view(ymd, fday, 1, line_ending)
+}
+
+/// The week containing a date and the `count - 1` weeks after it, one row
+/// each, under a header spanning their dates.
+pub fn weeks_view(
+    ymd: (i32, u32, u32),
+    fday: u8,
+    count: u32,
+    line_ending: LineEnding,
+) -> Option<String> {
+    let date = NaiveDate::from_ymd_opt(ymd.0, ymd.1, ymd.2)?;
+    let start = Weekday::from_u8(fday)?.pred();
+    let first = date.week(start).first_day();
+    let last = first.checked_add_signed(chrono::Duration::weeks(count.max(1) as i64))?;
+    let last = last.pred_opt()?;
+    let title = if first.year() == last.year() {
+        format!(
+            "{} - {} {}",
+            first.format("%-d %b"),
+            last.format("%-d %b"),
+            last.year()
+        )
+    } else {
+        format!(
+            "{} - {}",
+            first.format("%-d %b %Y"),
+            last.format("%-d %b %Y")
+        )
+    };
+    Some(week_strip(&title, first, last, Some(date), line_ending))
+}
+
+/// The seven days of an ISO week, Monday to Sunday, under a header like
+/// "2022-W45".
+pub fn iso_week_view(year: i32, week: u32, line_ending: LineEnding) -> Option<String> {
+    let first = NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)?;
+    let last = NaiveDate::from_isoywd_opt(year, week, Weekday::Sun)?;
+    let title = format!("{}-W{:02}", year, week);
+    Some(week_strip(&title, first, last, None, line_ending))
+}
+
+/// Rows of the weeks from `first` to `last` under `title` and the weekdays,
+/// `hlight` in reverse video, lines ended by `line_ending`.
+fn week_strip(
+    title: &str,
+    first: NaiveDate,
+    last: NaiveDate,
+    hlight: Option<NaiveDate>,
+    line_ending: LineEnding,
+) -> String {
+    let eol = line_ending.as_str();
+    let style = Style::default();
+    let rows = first
+        .iter_days()
+        .take_while(|&d| d <= last)
+        .map(|d| {
+            if Some(d) == hlight {
+                format!("{} ", day_cell(d, &style).reversed())
+            } else {
+                format!("{} ", day_cell(d, &style))
+            }
+        })
+        .chunks(7)
+        .into_iter()
+        .map(|mut week| week.join(""))
+        .join(eol);
+    format!(
+        "{:^4$}{3}{}{3}{}",
+        title,
+        weekday_line(first.weekday(), &style),
+        rows,
+        eol,
+        MONTH_WIDTH
+    )
+}
+
+/// Put a background behind a whole line, surviving the resets within it.
+fn tint(line: &str) -> String {
+    const TINT: &str = "\x1b[44m";
+    const RESET: &str = "\x1b[0m";
+    if !colored::control::SHOULD_COLORIZE.should_colorize() {
+        return line.to_string();
+    }
+    let inner = line.replace(RESET, &format!("{}{}", RESET, TINT));
+    format!("{}{}{}", TINT, inner, RESET)
+}
+
+/// Text with printable ASCII replaced by its full-width form, e.g. "11" by
+/// "１１", so that every column doubles. Lines and blocks are drawn twice,
+/// other characters followed by a space. Color codes are kept.
+fn full_width(text: &str) -> String {
+    let mut escaped = false;
+    let mut wide = String::with_capacity(3 * text.len());
+    for c in text.chars() {
+        if c == '\x1b' {
+            escaped = true;
+            wide.push(c);
+        } else if escaped {
+            escaped = c != 'm';
+            wide.push(c);
+        } else if c == ' ' {
+            wide.push('\u{3000}');
+        } else if c.is_ascii_graphic() {
+            wide.push(char::from_u32(c as u32 - 0x21 + 0xff01).unwrap());
+        } else if c == '·' {
+            wide.push('・');
+        } else if ('\u{2500}'..='\u{259f}').contains(&c) {
+            // box drawing and block elements join up when repeated
+            wide.extend([c, c]);
+        } else if c.is_ascii() {
+            wide.push(c);
+        } else {
+            wide.extend([c, ' ']);
+        }
+    }
+    wide
+}
+
+/// A line cut to `width` visible columns, its last one replaced by '…',
+/// if it is wider. Color codes are kept and closed.
+fn truncate(line: &str, width: usize) -> String {
+    let mut visible = 0;
+    let mut escaped = false;
+    let mut end = None;
+    for (i, c) in line.char_indices() {
+        if c == '\x1b' {
+            escaped = true;
+        } else if escaped {
+            escaped = c != 'm';
+        } else {
+            if visible + 1 == width {
+                end.get_or_insert(i);
+            }
+            visible += 1;
+        }
+    }
+    match end {
+        Some(end) if visible > width => {
+            let reset = if line.contains('\x1b') { "\x1b[0m" } else { "" };
+            format!("{}{}…", &line[..end], reset)
+        }
+        _ => line.to_string(),
+    }
+}
+
+/// Tokens joined by spaces into lines of at most `width` columns, unless a
+/// single token is wider.
+fn wrap(tokens: impl Iterator<Item = String>, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for token in tokens {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + token.chars().count() <= width => {
+                line.push(' ');
+                line.push_str(&token);
+            }
+            _ => lines.push(token),
+        }
+    }
+    lines
+}
+
+/// A CSV field, quoted per RFC 4180 when needed.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
 }
 
 /// Terminal width (max value is 80)
-fn term_width() -> usize {
+pub fn term_width() -> usize {
     const DEFAULT_TERM_WIDTH: usize = 80;
     match termsize::get() {
         Some(size) => (size.cols as usize).min(DEFAULT_TERM_WIDTH),
@@ -113,6 +1068,51 @@ fn term_width() -> usize {
     }
 }
 
+/// How rendered lines are terminated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+        }
+    }
+}
+
+/// Horizontal placement of a row of months.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// One grid cell of a displayed month, for structured exports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    /// the month whose grid holds the cell
+    pub month: MonthOfYear,
+
+    /// the day of the month, `None` for padding
+    pub day: Option<u8>,
+
+    /// the month and day a padding cell shows when the calendar shows days
+    /// of adjacent months
+    pub adjacent: Option<(MonthOfYear, u8)>,
+
+    pub weekday: Weekday,
+    pub is_weekend: bool,
+    pub is_today: bool,
+    pub is_highlight: bool,
+}
+
 pub struct Calendar {
     /// the queried date
     query: NaiveDate,
@@ -123,138 +1123,2339 @@ pub struct Calendar {
     /// whether to span the queried date
     span: bool,
 
-    /// display a whole year (overwrites `nmon` and `span`)
+    /// display whole years (overwrites `span`, `nmon` counts their months)
     year: bool,
 
     /// the first weekday
     fday: Weekday,
 
-    /// horizontal capacity of months
-    ncol: usize,
+    /// horizontal capacity of months, as many as fit the terminal if unset
+    ncol: Option<usize>,
 
     /// a date to highlight
     hlight: NaiveDate,
-}
 
-impl Calendar {
-    pub fn new(
-        ymd: (i32, u32, u32),
-        nmon: u32,
-        span: bool,
-        year: bool,
-        fday: u8,
-        ncol: Option<usize>,
-        hl: (i32, u32, u32),
-    ) -> Option<Self> {
-        Some(Self {
-            query: NaiveDate::from_ymd_opt(ymd.0, ymd.1, ymd.2)?,
-            nmon,
-            span,
-            year,
+    /// rendering options
+    style: Style,
+
+    /// line terminator between rows
+    line_ending: LineEnding,
+
+    /// drop trailing rows without days of the month in single-month view
+    trim: bool,
+
+    /// the current date
+    today: NaiveDate,
+
+    /// tint the block of the current month
+    mark_current_month: bool,
+
+    /// number weeks from the week of this date in a leading column
+    week_epoch: Option<NaiveDate>,
+
+    /// cut lines wider than this many columns
+    max_width: Option<usize>,
+
+    /// placement of a last row holding fewer months than the others
+    last_row_align: Align,
+
+    /// text centered above the calendar
+    title: Option<String>,
+
+    /// put the lines of the whole output in reverse order
+    vflip: bool,
+
+    /// write days as dots, except the current date
+    minimal: bool,
+
+    /// write with full-width characters, doubling every column
+    full_width: bool,
+
+    /// show days of adjacent months around a single month
+    adjacent_days: bool,
+
+    /// give structured exports the ISO week and day of the year of each day
+    export_numbers: bool,
+
+    /// character between side-by-side months, after a space in year view
+    month_separator: char,
+
+    /// lead the output with a line naming the current date
+    today_header: bool,
+
+    /// write year banners in block digits
+    block_banner: bool,
+
+    /// pad a single month with blank lines to at least this many
+    min_height: usize,
+
+    /// end the output with a line terminator
+    trailing_newline: bool,
+
+    /// years whose months may be displayed, any if unset
+    supported_years: Option<RangeInclusive<i32>>,
+}
+
+impl Calendar {
+    pub fn new(
+        ymd: (i32, u32, u32),
+        nmon: u32,
+        span: bool,
+        year: bool,
+        fday: u8,
+        ncol: Option<usize>,
+        hl: (i32, u32, u32),
+    ) -> Option<Self> {
+        Some(Self {
+            query: NaiveDate::from_ymd_opt(ymd.0, ymd.1, ymd.2)?,
+            nmon,
+            span,
+            year,
             fday: Weekday::from_u8(fday)?.pred(),
-            ncol: ncol
-                .unwrap_or(if year {
-                    (term_width() + 2) / (MONTH_WIDTH + 2)
-                } else {
-                    (term_width() + 1) / (MONTH_WIDTH + 1)
-                })
-                .max(1),
+            ncol,
             hlight: NaiveDate::from_ymd_opt(hl.0, hl.1, hl.2)?,
+            style: Style::default(),
+            line_ending: LineEnding::default(),
+            trim: false,
+            today: Local::now().date_naive(),
+            mark_current_month: false,
+            week_epoch: None,
+            max_width: None,
+            last_row_align: Align::default(),
+            title: None,
+            vflip: false,
+            minimal: false,
+            full_width: false,
+            adjacent_days: true,
+            export_numbers: false,
+            month_separator: ' ',
+            today_header: false,
+            block_banner: false,
+            min_height: 0,
+            trailing_newline: false,
+            supported_years: None,
+        })
+    }
+
+    /// The month of `date` with `date` highlighted, weeks starting on
+    /// Sunday.
+    pub fn for_date(date: NaiveDate) -> Self {
+        let ymd = (date.year(), date.month(), date.day());
+        Self::new(ymd, 1, false, false, 0, Some(1), ymd).unwrap()
+    }
+
+    /// List the weeks of each month bottom-up, headers staying on top.
+    pub fn with_flip(mut self, flip: bool) -> Self {
+        self.style.flip = flip;
+        self
+    }
+
+    /// Attach a value to each day for value-driven styling.
+    pub fn with_day_values(mut self, values: impl DayValue + 'static) -> Self {
+        self.style.values = Some(Rc::new(values));
+        self
+    }
+
+    /// Embolden the day with the largest value in each month.
+    pub fn with_mark_max(mut self, mark_max: bool) -> Self {
+        self.style.mark_max = mark_max;
+        self
+    }
+
+    /// Shade every other week row within each month.
+    pub fn with_zebra_weeks(mut self, zebra_weeks: bool) -> Self {
+        self.style.zebra_weeks = zebra_weeks;
+        self
+    }
+
+    /// Write day numbers in another radix, from 6 (so that 31 fits in two
+    /// digits) to 36.
+    pub fn with_radix(mut self, radix: u32) -> Option<Self> {
+        if !(6..=36).contains(&radix) {
+            return None;
+        }
+        self.style.radix = radix;
+        Some(self)
+    }
+
+    /// Drop trailing week rows holding no day of the month when showing a
+    /// single month. Multi-month views keep them to stay aligned.
+    pub fn with_trim(mut self, trim: bool) -> Self {
+        self.trim = trim;
+        self
+    }
+
+    /// End the output with a line terminator, as text files do. By default
+    /// the last line is left open.
+    pub fn with_trailing_newline(mut self, trailing_newline: bool) -> Self {
+        self.trailing_newline = trailing_newline;
+        self
+    }
+
+    /// Pad a single month with blank lines of its width to at least
+    /// `min_height` lines, headers included, for layouts of fixed size.
+    pub fn with_min_height(mut self, min_height: usize) -> Self {
+        self.min_height = min_height;
+        self
+    }
+
+    /// Append each month's number to its header, like "November 2022 (11)".
+    pub fn with_month_number(mut self, month_number: bool) -> Self {
+        self.style.month_number = month_number;
+        self
+    }
+
+    /// Mark the highlighted day with `prefix` (e.g. ">11") instead of reverse
+    /// video. Gaps then lead each cell instead of trailing it, keeping widths.
+    pub fn with_today_prefix(mut self, prefix: Option<char>) -> Self {
+        self.style.today_prefix = prefix;
+        self
+    }
+
+    /// Pin the current date instead of reading the clock.
+    pub fn with_today(mut self, ymd: (i32, u32, u32)) -> Option<Self> {
+        self.today = NaiveDate::from_ymd_opt(ymd.0, ymd.1, ymd.2)?;
+        Some(self)
+    }
+
+    /// Days from the current date to `ymd`, negative if it has passed.
+    pub fn days_until(&self, ymd: (i32, u32, u32)) -> Option<i64> {
+        let target = NaiveDate::from_ymd_opt(ymd.0, ymd.1, ymd.2)?;
+        Some(target.signed_duration_since(self.today).num_days())
+    }
+
+    /// The current date like "Friday, 11 November 2022".
+    fn today_text(&self) -> String {
+        self.today.format("%A, %-d %B %Y").to_string()
+    }
+
+    /// Write the banners of year views in block digits five rows tall.
+    pub fn with_block_banner(mut self, block_banner: bool) -> Self {
+        self.block_banner = block_banner;
+        self
+    }
+
+    /// The lines of a year's banner, before centering.
+    fn banner(&self, year: i32) -> Vec<String> {
+        if self.block_banner {
+            block_digits(&year.to_string())
+        } else {
+            vec![year.to_string()]
+        }
+    }
+
+    /// Lead the output with a line like "Today: Friday, 11 November 2022".
+    pub fn with_today_header(mut self, today_header: bool) -> Self {
+        self.today_header = today_header;
+        self
+    }
+
+    /// A line about the current date like "Friday, 11 November 2022 — day
+    /// 315 of 365, week 45". Weeks start on the calendar's first weekday,
+    /// the first full one of the year being week 1 as with `%U`.
+    pub fn today_line(&self) -> String {
+        let today = self.today;
+        let wday =
+            (7 + today.weekday().num_days_from_monday() - self.fday.num_days_from_monday()) % 7;
+        let days = Year::new(today.year()).map_or(365, |y| y.num_days());
+        format!(
+            "{} — day {} of {}, week {}",
+            self.today_text(),
+            today.ordinal(),
+            days,
+            (today.ordinal0() + 7 - wday) / 7
+        )
+    }
+
+    /// Tint the whole block of the current month, if it is displayed.
+    pub fn with_mark_current_month(mut self, mark: bool) -> Self {
+        self.mark_current_month = mark;
+        self
+    }
+
+    /// Show `count` whole years, starting with the queried one, each under
+    /// its own banner. Fails if the last of them is out of range.
+    pub fn with_years(mut self, count: u32) -> Option<Self> {
+        let count = count.max(1);
+        let last = i32::try_from(count - 1).ok()?;
+        Year::new(self.query.year().checked_add(last)?)?;
+        self.year = true;
+        self.nmon = count.checked_mul(12)?;
+        self.check_supported_years()
+    }
+
+    /// Show the twelve months of the academic year holding the queried
+    /// date, starting with `start_month` (September at most schools), under
+    /// a title like "2022–2023 Academic Year" unless one is set.
+    pub fn with_academic_year(mut self, start_month: u32) -> Option<Self> {
+        let year = if self.query.month() >= start_month {
+            self.query.year()
+        } else {
+            self.query.year() - 1
+        };
+        self.query = NaiveDate::from_ymd_opt(year, start_month, 1)?;
+        self.year = false;
+        self.span = false;
+        self.nmon = 12;
+        self.title
+            .get_or_insert_with(|| format!("{}–{} Academic Year", year, year + 1));
+        self.check_supported_years()
+    }
+
+    /// Paint Saturdays and Sundays red (the default). Highlights are kept
+    /// either way.
+    pub fn with_weekend_color(mut self, color: bool) -> Self {
+        self.style.color_weekends = color;
+        self
+    }
+
+    /// Number week rows in a leading column, counting from 1 for the week
+    /// containing `ymd`, as fiscal or academic calendars do. The column
+    /// widens to fit the largest number shown.
+    pub fn with_week_epoch(mut self, ymd: (i32, u32, u32)) -> Option<Self> {
+        self.week_epoch = Some(NaiveDate::from_ymd_opt(ymd.0, ymd.1, ymd.2)?);
+        Some(self)
+    }
+
+    /// Width of a month block, including any week-number column.
+    fn block_width(&self) -> usize {
+        self.style.month_width() + self.label_width()
+    }
+
+    /// Width of the week-number column, a gap included: room for two
+    /// digits, or as many as the last week shown needs.
+    fn label_width(&self) -> usize {
+        let epoch = match self.week_epoch {
+            Some(epoch) => epoch.week(self.fday).first_day(),
+            None => return 0,
+        };
+        let (y, m, d) = self.date_bounds().1;
+        let last = NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let number = last.signed_duration_since(epoch).num_weeks() + 1;
+        number.to_string().len().max(2) + 1
+    }
+
+    /// The number of months side by side. Unless set, as many as fit the
+    /// terminal, measured with the final width of the blocks.
+    fn ncol(&self) -> usize {
+        let width = self.block_width();
+        let term = term_width() / self.scale();
+        self.ncol
+            .unwrap_or(if self.year {
+                (term + 2) / (width + 2)
+            } else {
+                (term + 1) / (width + 1)
+            })
+            .max(1)
+    }
+
+    /// How many columns each rendered character takes in the output.
+    fn scale(&self) -> usize {
+        if self.full_width {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// The week-number column of a month block, blank beside the headers
+    /// and padding rows.
+    fn week_labels(&self, m: NaiveDate) -> Vec<String> {
+        let epoch = match self.week_epoch {
+            Some(epoch) => epoch.week(self.fday).first_day(),
+            None => return vec![String::new(); self.style.month_rows()],
+        };
+        let width = self.label_width();
+        let blank = " ".repeat(width);
+        let nweeks = week_rows(m, self.fday);
+        let mut labels = m
+            .with_day(1)
+            .unwrap()
+            .iter_weeks()
+            .take(DAY_ROWS)
+            .enumerate()
+            .map(|(i, d)| {
+                let start = d.week(self.fday).first_day();
+                let number = start.signed_duration_since(epoch).num_weeks() + 1;
+                // weeks before the epoch are left unnumbered
+                if (i < nweeks || self.hint()) && number >= 1 {
+                    format!("{:>1$} ", number, width - 1).dimmed().to_string()
+                } else {
+                    blank.clone()
+                }
+            })
+            .collect_vec();
+        if self.style.flip {
+            labels[..nweeks].reverse();
+        }
+        vec![blank.clone(); self.style.header_rows()]
+            .into_iter()
+            .chain(self.style.divide(labels, &blank))
+            .collect()
+    }
+
+    /// Draw each day as a checkbox for habit tracking: "[x]" where the day
+    /// value is positive, "[ ]" elsewhere. Cells widen to fit.
+    pub fn with_checkbox(mut self, checkbox: bool) -> Self {
+        self.style.checkbox = checkbox;
+        self
+    }
+
+    /// Cut lines wider than `width` columns, ending them with '…', for
+    /// terminals too narrow for even one month.
+    pub fn with_max_width(mut self, width: Option<usize>) -> Self {
+        self.max_width = width.map(|w| w.max(1));
+        self
+    }
+
+    /// A rendered line, cut to the maximum width if one is set.
+    fn fit(&self, line: String) -> String {
+        match self.max_width {
+            // full-width output doubles the line after the cut
+            Some(width) => truncate(&line, (width / self.scale()).max(1)),
+            None => line,
+        }
+    }
+
+    /// Head columns with `names`, from Sunday. Every cell widens to the
+    /// longest name so that days stay under their weekdays.
+    pub fn with_weekday_names(mut self, names: [&str; 7]) -> Self {
+        self.style.weekday_names = Some(names.map(String::from));
+        self
+    }
+
+    /// Write each day cell with `text`, given the year, month, day and
+    /// weekday. Texts wider than the cell are cut, narrower ones right-aligned.
+    pub fn with_day_text(
+        mut self,
+        text: impl Fn(i32, u32, u32, Weekday) -> String + 'static,
+    ) -> Self {
+        self.style.day_text = Some(Rc::new(text));
+        self
+    }
+
+    /// Give days their own effects, like those of `parse_day_effects`.
+    pub fn with_day_effects(mut self, effects: &HashMap<(i32, u32, u32), Effect>) -> Option<Self> {
+        for (&(y, m, d), &effect) in effects {
+            let date = NaiveDate::from_ymd_opt(y, m, d)?;
+            self.style.day_effects.insert(date, effect);
+        }
+        Some(self)
+    }
+
+    /// Highlight with `effect` the days of the shown months for which
+    /// `predicate`, given the year, month, day and weekday, holds.
+    pub fn with_highlight_if(
+        mut self,
+        predicate: impl Fn(i32, u32, u32, Weekday) -> bool + 'static,
+        effect: Effect,
+    ) -> Self {
+        self.style.highlight_if = Some((Rc::new(predicate), effect));
+        self
+    }
+
+    /// Leave `gap` blank lines between the weekday row and the first week.
+    pub fn with_header_gap(mut self, gap: usize) -> Self {
+        self.style.header_gap = gap;
+        self
+    }
+
+    /// Set how days of the previous and the next month look where a single
+    /// month shows them, both dimmed by default.
+    pub fn with_overflow_effects(mut self, prev: Effect, next: Effect) -> Self {
+        self.style.prev_overflow = prev;
+        self.style.next_overflow = next;
+        self
+    }
+
+    /// Keep day numbers to the left or, as by default, the right of cells.
+    pub fn with_day_align(mut self, align: DayAlign) -> Self {
+        self.style.day_align = align;
+        self
+    }
+
+    /// Write weekday names in another letter case.
+    pub fn with_weekday_case(mut self, case: WeekdayCase) -> Self {
+        self.style.weekday_case = case;
+        self
+    }
+
+    /// Highlight the days from `first` to `last`, possibly across months,
+    /// telling the endpoints from the days between by `style`.
+    pub fn with_highlight_range(
+        mut self,
+        first: (i32, u32, u32),
+        last: (i32, u32, u32),
+        style: RangeHighlightStyle,
+    ) -> Option<Self> {
+        let first = NaiveDate::from_ymd_opt(first.0, first.1, first.2)?;
+        let last = NaiveDate::from_ymd_opt(last.0, last.1, last.2)?;
+        if first > last {
+            return None;
+        }
+        self.style.range = Some((first, last));
+        self.style.range_style = style;
+        Some(self)
+    }
+
+    /// Draw a horizontal line between week rows, without any border.
+    pub fn with_week_dividers(mut self, week_dividers: bool) -> Self {
+        self.style.week_dividers = week_dividers;
+        self
+    }
+
+    /// Place a last row of fewer months than the columns, left-aligned by
+    /// default, under the rows above.
+    pub fn with_last_row_align(mut self, align: Align) -> Self {
+        self.last_row_align = align;
+        self
+    }
+
+    /// Reverse the order of all output lines, headers included, so that the
+    /// calendar reads right from the back of a transparent print. Unlike
+    /// `with_flip`, this also moves the headers to the bottom.
+    pub fn with_vflip(mut self, vflip: bool) -> Self {
+        self.vflip = vflip;
+        self
+    }
+
+    /// Write every day as a '·' except the current date, for ambient
+    /// displays.
+    pub fn with_minimal(mut self, minimal: bool) -> Self {
+        self.minimal = minimal;
+        self
+    }
+
+    /// Write everything with full-width characters ("１１" for 11), for large
+    /// displays. Every column doubles, so alignment is kept, and the columns
+    /// and widths that fit the terminal halve.
+    pub fn with_full_width(mut self, full_width: bool) -> Self {
+        self.full_width = full_width;
+        self
+    }
+
+    /// Show days of the adjacent months around a single month, as by
+    /// default. Views of several months never show them.
+    pub fn with_adjacent_days(mut self, adjacent_days: bool) -> Self {
+        self.adjacent_days = adjacent_days;
+        self
+    }
+
+    /// Put `separator`, like '│', between side-by-side months instead of a
+    /// blank column. It should be one column wide.
+    pub fn with_month_separator(mut self, separator: char) -> Self {
+        self.month_separator = separator;
+        self
+    }
+
+    /// Start weeks `delta` weekdays later, or earlier if negative.
+    pub fn rotate_first_weekday(mut self, delta: i8) -> Self {
+        let days = self.fday.num_days_from_monday() as i32 + delta as i32;
+        self.fday = Weekday::from_u8(days.rem_euclid(7) as u8).unwrap();
+        self
+    }
+
+    /// Add the ISO week number and the day of the year of each day to
+    /// structured exports like CSV.
+    pub fn with_export_numbers(mut self, numbers: bool) -> Self {
+        self.export_numbers = numbers;
+        self
+    }
+
+    /// Whether days of adjacent months fill the padding cells.
+    fn hint(&self) -> bool {
+        self.nmon == 1 && self.adjacent_days
+    }
+
+    /// The earliest and latest dates shown, adjacent month days included.
+    pub fn date_bounds(&self) -> ((i32, u32, u32), (i32, u32, u32)) {
+        let ymd = |d: NaiveDate| (d.year(), d.month(), d.day());
+        let first = self.iter_days().next().unwrap();
+        if self.hint() && !self.style.checkbox {
+            let start = first.week(self.fday).first_day();
+            let rows = if self.trim {
+                week_rows(first, self.fday)
+            } else {
+                DAY_ROWS
+            };
+            let end = start + chrono::Duration::days(7 * rows as i64 - 1);
+            return (ymd(start), ymd(end));
+        }
+        let last = self.iter_days().last().unwrap();
+        (ymd(first), ymd(last))
+    }
+
+    /// Center `title` above the calendar, wrapping it to the calendar width.
+    pub fn with_title(mut self, title: Option<String>) -> Self {
+        self.title = title;
+        self
+    }
+
+    /// Refuse displaying any month out of `years`, e.g. `1..=9999` to keep
+    /// headers at their usual widths. The setters that later choose other
+    /// months fail the same way.
+    pub fn with_supported_years(mut self, years: RangeInclusive<i32>) -> Option<Self> {
+        self.supported_years = Some(years);
+        self.check_supported_years()
+    }
+
+    /// The calendar, unless it displays a month out of the supported years.
+    fn check_supported_years(self) -> Option<Self> {
+        if let Some(years) = &self.supported_years {
+            if !self.iter_month().all(|m| years.contains(&m.year())) {
+                return None;
+            }
+        }
+        Some(self)
+    }
+
+    /// The layout and styling options as a compact string like
+    /// "date=2022-11-01;months=3;first=0;columns=3;highlight=2022-11-11;flip",
+    /// for `from_config_string` to rebuild the calendar elsewhere. Options
+    /// carrying text, day values or closures are left out.
+    pub fn to_config_string(&self) -> String {
+        let date = |d: NaiveDate| d.format("%Y-%m-%d").to_string();
+        let effect = |e: Effect| match e {
+            Effect::Plain => "plain",
+            Effect::Dimmed => "dim",
+            Effect::Italic => "italic",
+            Effect::Bold => "bold",
+            Effect::Underline => "underline",
+            Effect::Reversed => "reverse",
+        };
+        let style = &self.style;
+        let mut fields = vec![
+            format!("date={}", date(self.query)),
+            format!("months={}", self.nmon),
+            format!("first={}", self.fday.succ().num_days_from_monday()),
+            format!("columns={}", self.ncol()),
+            format!("highlight={}", date(self.hlight)),
+        ];
+        let flags = [
+            ("span", self.span),
+            ("year", self.year),
+            ("flip", style.flip),
+            ("vflip", self.vflip),
+            ("minimal", self.minimal),
+            ("full-width", self.full_width),
+            ("no-adjacent", !self.adjacent_days),
+            ("export-numbers", self.export_numbers),
+            ("today-header", self.today_header),
+            ("block-banner", self.block_banner),
+            ("trailing-newline", self.trailing_newline),
+            ("mark-max", style.mark_max),
+            ("zebra", style.zebra_weeks),
+            ("month-number", style.month_number),
+            ("no-weekend-color", !style.color_weekends),
+            ("checkbox", style.checkbox),
+            ("dividers", style.week_dividers),
+            ("trim", self.trim),
+            ("mark-month", self.mark_current_month),
+            ("crlf", self.line_ending == LineEnding::Crlf),
+        ];
+        fields.extend(flags.iter().filter(|f| f.1).map(|f| f.0.to_string()));
+        if style.radix != 10 {
+            fields.push(format!("radix={}", style.radix));
+        }
+        if let Some(prefix) = style.today_prefix {
+            // as a code point, so that any character survives
+            fields.push(format!("today-prefix={}", prefix as u32));
+        }
+        if self.month_separator != ' ' {
+            fields.push(format!("separator={}", self.month_separator as u32));
+        }
+        if self.min_height > 0 {
+            fields.push(format!("min-height={}", self.min_height));
+        }
+        if style.header_gap > 0 {
+            fields.push(format!("header-gap={}", style.header_gap));
+        }
+        if (style.prev_overflow, style.next_overflow) != (Effect::Dimmed, Effect::Dimmed) {
+            fields.push(format!("prev-days={}", effect(style.prev_overflow)));
+            fields.push(format!("next-days={}", effect(style.next_overflow)));
+        }
+        if let Some(epoch) = self.week_epoch {
+            fields.push(format!("week-epoch={}", date(epoch)));
+        }
+        if let Some(width) = self.max_width {
+            fields.push(format!("max-width={}", width));
+        }
+        if let Some((first, last)) = style.range {
+            fields.push(format!("range={}..{}", date(first), date(last)));
+            fields.push(format!(
+                "range-ends={}",
+                effect(style.range_style.endpoints)
+            ));
+            fields.push(format!(
+                "range-inside={}",
+                effect(style.range_style.interior)
+            ));
+        }
+        if let Some(years) = &self.supported_years {
+            fields.push(format!(
+                "supported-years={}..{}",
+                years.start(),
+                years.end()
+            ));
+        }
+        if !style.day_effects.is_empty() {
+            let days = style
+                .day_effects
+                .iter()
+                .sorted_by_key(|day| day.0)
+                .map(|(&d, &e)| format!("{}:{}", date(d), effect(e)))
+                .join(",");
+            fields.push(format!("day-effects={}", days));
+        }
+        if style.day_align == DayAlign::Left {
+            fields.push("day-align=left".to_string());
+        }
+        match style.weekday_case {
+            WeekdayCase::Title => {}
+            WeekdayCase::Upper => fields.push("weekday-case=upper".to_string()),
+            WeekdayCase::Lower => fields.push("weekday-case=lower".to_string()),
+        }
+        match self.last_row_align {
+            Align::Left => {}
+            Align::Center => fields.push("last-row=center".to_string()),
+            Align::Right => fields.push("last-row=right".to_string()),
+        }
+        fields.join(";")
+    }
+
+    /// Rebuild a calendar from `to_config_string`, or `None` if the string
+    /// is malformed.
+    pub fn from_config_string(config: &str) -> Option<Self> {
+        let fields: Vec<_> = config
+            .split(';')
+            .map(|f| f.split_once('=').unwrap_or((f, "")))
+            .collect();
+        let get = |key: &str| fields.iter().find(|f| f.0 == key).map(|f| f.1);
+        let has = |key: &str| get(key).is_some();
+        let effect = |key: &str| match get(key) {
+            None | Some("dim") => Some(Effect::Dimmed),
+            Some("plain") => Some(Effect::Plain),
+            Some("italic") => Some(Effect::Italic),
+            Some("bold") => Some(Effect::Bold),
+            Some("underline") => Some(Effect::Underline),
+            Some("reverse") => Some(Effect::Reversed),
+            Some(_) => None,
+        };
+        let mut cal = Calendar::new(
+            parse_date(get("date")?)?,
+            get("months")?.parse().ok()?,
+            has("span"),
+            has("year"),
+            get("first")?.parse().ok()?,
+            Some(get("columns")?.parse().ok()?),
+            parse_date(get("highlight")?)?,
+        )?
+        .with_flip(has("flip"))
+        .with_vflip(has("vflip"))
+        .with_minimal(has("minimal"))
+        .with_full_width(has("full-width"))
+        .with_adjacent_days(!has("no-adjacent"))
+        .with_export_numbers(has("export-numbers"))
+        .with_today_header(has("today-header"))
+        .with_block_banner(has("block-banner"))
+        .with_trailing_newline(has("trailing-newline"))
+        .with_mark_max(has("mark-max"))
+        .with_zebra_weeks(has("zebra"))
+        .with_month_number(has("month-number"))
+        .with_weekend_color(!has("no-weekend-color"))
+        .with_checkbox(has("checkbox"))
+        .with_week_dividers(has("dividers"))
+        .with_trim(has("trim"))
+        .with_mark_current_month(has("mark-month"))
+        .with_overflow_effects(effect("prev-days")?, effect("next-days")?)
+        .with_radix(get("radix").unwrap_or("10").parse().ok()?)?
+        .with_header_gap(get("header-gap").unwrap_or("0").parse().ok()?);
+        if has("crlf") {
+            cal = cal.with_line_ending(LineEnding::Crlf);
+        }
+        if let Some(prefix) = get("today-prefix") {
+            cal = cal.with_today_prefix(Some(char::from_u32(prefix.parse().ok()?)?));
+        }
+        if let Some(height) = get("min-height") {
+            cal = cal.with_min_height(height.parse().ok()?);
+        }
+        if let Some(separator) = get("separator") {
+            cal = cal.with_month_separator(char::from_u32(separator.parse().ok()?)?);
+        }
+        if let Some(epoch) = get("week-epoch") {
+            cal = cal.with_week_epoch(parse_date(epoch)?)?;
+        }
+        if let Some(width) = get("max-width") {
+            cal = cal.with_max_width(Some(width.parse().ok()?));
+        }
+        if let Some(range) = get("range") {
+            let (first, last) = range.split_once("..")?;
+            let style = RangeHighlightStyle {
+                endpoints: get("range-ends")?.parse().ok()?,
+                interior: get("range-inside")?.parse().ok()?,
+            };
+            cal = cal.with_highlight_range(parse_date(first)?, parse_date(last)?, style)?;
+        }
+        if let Some(days) = get("day-effects") {
+            let mut effects = HashMap::new();
+            for day in days.split(',') {
+                let (date, effect) = day.split_once(':')?;
+                effects.insert(parse_date(date)?, effect.parse().ok()?);
+            }
+            cal = cal.with_day_effects(&effects)?;
+        }
+        if let Some(years) = get("supported-years") {
+            let (first, last) = years.split_once("..")?;
+            cal = cal.with_supported_years(first.parse().ok()?..=last.parse().ok()?)?;
+        }
+        let day_align = match get("day-align") {
+            None => DayAlign::Right,
+            Some("left") => DayAlign::Left,
+            Some(_) => return None,
+        };
+        let case = match get("weekday-case") {
+            None => WeekdayCase::Title,
+            Some("upper") => WeekdayCase::Upper,
+            Some("lower") => WeekdayCase::Lower,
+            Some(_) => return None,
+        };
+        let align = match get("last-row") {
+            None => Align::Left,
+            Some("center") => Align::Center,
+            Some("right") => Align::Right,
+            Some(_) => return None,
+        };
+        Some(
+            cal.with_day_align(day_align)
+                .with_weekday_case(case)
+                .with_last_row_align(align),
+        )
+    }
+
+    /// Separate rows with `\r\n` instead of `\n`.
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    fn iter_month(&self) -> impl Iterator<Item = NaiveDate> {
+        let start = if self.year {
+            self.query.with_ordinal(1).unwrap()
+        } else if self.span {
+            self.query - Months::new(self.nmon / 2)
+        } else {
+            self.query
+        };
+        // the month after the last one may be past chrono's range
+        std::iter::successors(Some(start), |d| d.checked_add_months(Months::new(1)))
+            .take(self.nmon as usize)
+    }
+
+    /// Every day of the displayed months, in order.
+    fn iter_days(&self) -> impl Iterator<Item = NaiveDate> {
+        self.iter_month().flat_map(|m| {
+            let first = m.with_day(1).unwrap();
+            first
+                .iter_days()
+                .take_while(move |d| d.month() == first.month())
+        })
+    }
+
+    /// Every day of the displayed months, in order, with whether it is the
+    /// first and whether it is the last of its month.
+    pub fn iter_days_marked(&self) -> impl Iterator<Item = (NaiveDate, bool, bool)> {
+        self.iter_days().map(|d| {
+            let last = d.succ_opt().is_none_or(|next| next.month() != d.month());
+            (d, d.day() == 1, last)
         })
     }
 
-    fn iter_month(&self) -> impl Iterator<Item = NaiveDate> {
-        let start = if self.year {
-            self.query.with_ordinal(1).unwrap()
-        } else if self.span {
-            self.query - Months::new(self.nmon / 2)
-        } else {
-            self.query
+    /// Every grid cell of the displayed months, row by row, six rows each.
+    pub fn cells(&self) -> Vec<Cell> {
+        let hint = self.hint() && !self.style.checkbox;
+        self.iter_month()
+            .flat_map(|m| {
+                let month = MonthOfYear::new(m.year(), m.month()).unwrap();
+                let first = month.first_day();
+                first
+                    .week(self.fday)
+                    .first_day()
+                    .iter_days()
+                    .take(7 * DAY_ROWS)
+                    .map(move |d| {
+                        let in_month = d.month() == first.month();
+                        Cell {
+                            month,
+                            day: in_month.then_some(d.day() as u8),
+                            adjacent: (hint && !in_month).then(|| {
+                                (
+                                    MonthOfYear::new(d.year(), d.month()).unwrap(),
+                                    d.day() as u8,
+                                )
+                            }),
+                            weekday: d.weekday(),
+                            is_weekend: matches!(d.weekday(), Weekday::Sat | Weekday::Sun),
+                            is_today: in_month && d == self.today,
+                            is_highlight: in_month && d == self.hlight,
+                        }
+                    })
+            })
+            .collect()
+    }
+
+    /// The number of grid cells of the displayed months, six week rows
+    /// each, and how many of them hold a day of their month.
+    pub fn cell_stats(&self) -> (usize, usize) {
+        let cells = self.cells();
+        let filled = cells.iter().filter(|c| c.day.is_some()).count();
+        (cells.len(), filled)
+    }
+
+    /// The displayed months one below another as printable planner pages,
+    /// blank boxes numbered in their corners.
+    pub fn to_planner(&self, style: PlannerStyle) -> String {
+        let eol = self.line_ending.as_str();
+        self.iter_month()
+            .map(|m| planner(m, self.fday, style).join(eol))
+            .join(&eol.repeat(2))
+    }
+
+    /// The day numbers of the displayed months as packed by the text layout:
+    /// one entry per row of months, holding its six week rows, each running
+    /// across all months of the row. Padding cells are `None`.
+    pub fn grid(&self) -> Vec<Vec<Vec<Option<u8>>>> {
+        let months = self
+            .cells()
+            .chunks(7 * DAY_ROWS)
+            .map(|cells| cells.iter().map(|c| c.day).collect_vec())
+            .collect_vec();
+        months
+            .chunks(self.ncol())
+            .map(|row| {
+                (0..DAY_ROWS)
+                    .map(|week| {
+                        row.iter()
+                            .flat_map(|m| m[7 * week..7 * (week + 1)].iter().copied())
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Each displayed month rendered alone, with the options of the calendar.
+    pub fn month_strings(&self) -> Vec<(MonthOfYear, String)> {
+        self.iter_month()
+            .map(|m| {
+                let month = MonthOfYear::new(m.year(), m.month()).unwrap();
+                (month, self.format(&[m]))
+            })
+            .collect()
+    }
+
+    /// Write each displayed month to its own file, named by filling
+    /// `pattern` as `fill_pattern` does. Every file is attempted, and the
+    /// outcome for each path returned.
+    pub fn write_split(&self, pattern: &str) -> Vec<(String, std::io::Result<()>)> {
+        let eol = self.line_ending.as_str();
+        self.month_strings()
+            .into_iter()
+            .map(|(month, text)| {
+                let path = fill_pattern(pattern, month);
+                let result = std::fs::write(&path, text + eol);
+                (path, result)
+            })
+            .collect()
+    }
+
+    /// One CSV row per displayed day, with a header row and CRLF line breaks
+    /// as RFC 4180 specifies. With export numbers, rows end with the ISO week
+    /// and the day of the year.
+    pub fn to_csv(&self) -> String {
+        let mut header = vec!["year", "month", "day", "weekday", "is_weekend", "is_today"];
+        if self.export_numbers {
+            header.extend(["iso_week", "day_of_year"]);
+        }
+        let rows = self.iter_days().map(|d| {
+            let weekend = matches!(d.weekday(), Weekday::Sat | Weekday::Sun);
+            let mut fields = vec![
+                d.year().to_string(),
+                d.month().to_string(),
+                d.day().to_string(),
+                d.weekday().to_string(),
+                weekend.to_string(),
+                (d == self.today).to_string(),
+            ];
+            if self.export_numbers {
+                fields.push(d.iso_week().week().to_string());
+                fields.push(d.ordinal().to_string());
+            }
+            fields.iter().map(|f| csv_field(f)).join(",")
+        });
+        std::iter::once(header.join(","))
+            .chain(rows)
+            .map(|row| row + "\r\n")
+            .collect()
+    }
+
+    /// The number of displayed days, adjacent month days aside.
+    pub fn day_count(&self) -> usize {
+        self.iter_days().count()
+    }
+
+    /// The number of distinct ISO weeks the displayed days touch.
+    pub fn iso_week_count(&self) -> usize {
+        self.iter_days()
+            .map(|d| {
+                let week = d.iso_week();
+                (week.year(), week.week())
+            })
+            .unique()
+            .count()
+    }
+
+    /// All displayed days on one timeline like "Nov  1  2 ... 30 | Dec  1",
+    /// wrapped to `width` columns.
+    pub fn to_strip(&self, width: usize) -> String {
+        let first = self.iter_days().next();
+        let tokens = self.iter_days().flat_map(|d| {
+            let day = format!("{:>2}", d.day());
+            if d.day() != 1 {
+                return vec![day];
+            }
+            let month = Month::from_u32(d.month()).unwrap().name()[..3].to_string();
+            if Some(d) == first {
+                vec![month, day]
+            } else {
+                vec!["|".to_string(), month, day]
+            }
+        });
+        wrap(tokens, width).join(self.line_ending.as_str())
+    }
+
+    /// The width and height of the rendered calendar, below any title.
+    pub fn dimensions(&self) -> (usize, usize) {
+        let (width, height) = self.grid_size();
+        (width * self.scale(), height)
+    }
+
+    /// The size of the calendar in characters, before any full-width
+    /// doubling.
+    fn grid_size(&self) -> (usize, usize) {
+        let months = self.iter_month().count();
+        if self.year {
+            let width = self.ncol() * self.block_width() + (self.ncol() - 1) * 2;
+            // years as `render` chunks them, the last one maybe partial
+            let banner_rows = self.banner(self.query.year()).len() + 1;
+            let years = months.div_ceil(12);
+            let rows: usize = (0..years)
+                .map(|i| (months - 12 * i).min(12).div_ceil(self.ncol()) * self.block_rows())
+                .sum();
+            (width, years * banner_rows + rows + years.saturating_sub(1))
+        } else {
+            let cols = self.ncol().min(months);
+            let width = cols * self.block_width() + cols - 1;
+            (width, months.div_ceil(self.ncol()) * self.block_rows())
+        }
+    }
+
+    /// A year at a glance as seven rows, one per weekday, of one block per
+    /// day, like a contribution graph. Blocks darken from '░' to '█' with
+    /// the day value relative to the year's largest; days without a positive
+    /// value are '·'.
+    pub fn to_heatmap(&self, year: i32, values: &dyn DayValue) -> Option<String> {
+        let year = Year::new(year)?;
+        let first = NaiveDate::from_ymd_opt(year.year(), 1, 1)?;
+        let days = first.iter_days().take(year.num_days() as usize);
+        let value = |d: NaiveDate| values.value((d.year(), d.month(), d.day())).unwrap_or(0);
+        let max = days.map(value).max().unwrap_or(0);
+        let start = first.week(self.fday).first_day();
+        let last = NaiveDate::from_ymd_opt(year.year(), 12, 31)?;
+        let weeks = last.signed_duration_since(start).num_weeks() as usize + 1;
+        let mut rows = (0..7).map(|row| {
+            (0..weeks)
+                .map(|week| {
+                    let d = start + chrono::Duration::days((7 * week + row) as i64);
+                    if d.year() != year.year() {
+                        return " ".to_string();
+                    }
+                    match value(d) {
+                        v if v <= 0 => "·".bright_black().to_string(),
+                        // quartiles of the largest value
+                        v => {
+                            let level = ((v * 4 - 1) / max).min(3) as usize;
+                            ["░", "▒", "▓", "█"][level].green().to_string()
+                        }
+                    }
+                })
+                .join("")
+        });
+        Some(rows.join(self.line_ending.as_str()))
+    }
+
+    /// One line per day like "2022-W45-5" (ISO year, week and weekday).
+    pub fn to_iso_week_list(&self) -> String {
+        self.iter_days()
+            .map(|d| {
+                let week = d.iso_week();
+                format!(
+                    "{}-W{:02}-{}",
+                    week.year(),
+                    week.week(),
+                    d.weekday().number_from_monday()
+                )
+            })
+            .join(self.line_ending.as_str())
+    }
+
+    /// One line per day like "2022-11-08 100%", the lit share of the moon.
+    pub fn to_moon_list(&self) -> String {
+        self.iter_days()
+            .map(|d| {
+                let lit = moon_illumination((d.year(), d.month(), d.day())).unwrap();
+                format!("{} {:>3.0}%", d.format("%Y-%m-%d"), lit * 100.0)
+            })
+            .join(self.line_ending.as_str())
+    }
+
+    /// The number of lines of each month block.
+    fn block_rows(&self) -> usize {
+        // padding rows only matter for aligning months side by side
+        let rows = match self.iter_month().next() {
+            Some(m) if self.trim && self.nmon == 1 => {
+                self.style.header_rows() + self.style.day_rows(week_rows(m, self.fday))
+            }
+            _ => self.style.month_rows(),
+        };
+        if self.nmon == 1 {
+            rows.max(self.min_height)
+        } else {
+            rows
+        }
+    }
+
+    fn format(&self, months: &[NaiveDate]) -> String {
+        let rows = self.block_rows();
+        let style = Style {
+            minimal: self.minimal.then_some(self.today),
+            ..self.style.clone()
+        };
+        months
+            .iter()
+            .map(|&m| {
+                let current = self.mark_current_month
+                    && (m.year(), m.month()) == (self.today.year(), self.today.month());
+                calendar(m, self.fday, self.year, self.hlight, self.hint(), &style)
+                    .zip(self.week_labels(m))
+                    .map(move |(line, label)| {
+                        let line = label + &line;
+                        if current {
+                            tint(&line)
+                        } else {
+                            line
+                        }
+                    })
+                    // blank lines below, for a minimum height
+                    .chain(std::iter::repeat(" ".repeat(self.block_width())))
+            })
+            .collect_vec()
+            .chunks_mut(self.ncol())
+            .flat_map(|vec_of_iters| {
+                let sep = if self.year {
+                    format!(" {}", self.month_separator)
+                } else {
+                    self.month_separator.to_string()
+                };
+                let sep_width = sep.chars().count();
+                // a partial row may be shifted under the full ones
+                let missing = self.ncol() - vec_of_iters.len();
+                let indent = match self.last_row_align {
+                    Align::Left => 0,
+                    Align::Center => missing * (self.block_width() + sep_width) / 2,
+                    Align::Right => missing * (self.block_width() + sep_width),
+                };
+                (0..rows).map(move |_| {
+                    let line = vec_of_iters
+                        .iter_mut()
+                        .map(|it| it.next().unwrap())
+                        .join(&sep);
+                    self.fit(" ".repeat(indent) + &line)
+                })
+            })
+            .join(self.line_ending.as_str())
+    }
+}
+
+impl std::fmt::Display for Calendar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let eol = self.line_ending.as_str();
+        if !self.vflip && !self.full_width {
+            self.render(f)?;
+        } else {
+            let mut out = String::new();
+            self.render(&mut out)?;
+            if self.full_width {
+                out = full_width(&out);
+            }
+            if self.vflip {
+                out = out.split(eol).collect_vec().iter().rev().join(eol);
+            }
+            write!(f, "{}", out)?;
+        }
+        if self.trailing_newline {
+            write!(f, "{}", eol)?;
+        }
+        Ok(())
+    }
+}
+
+impl Calendar {
+    /// The whole output, top to bottom.
+    fn render(&self, f: &mut impl std::fmt::Write) -> std::fmt::Result {
+        let months = self.iter_month().collect_vec();
+        if self.today_header {
+            let line = self.fit(format!("Today: {}", self.today_text()));
+            write!(f, "{}{}", line, self.line_ending.as_str())?;
+        }
+        if let Some(title) = &self.title {
+            let width = self.grid_size().0;
+            for line in title.lines() {
+                let words = line.split_whitespace().map(String::from);
+                for line in wrap(words, width) {
+                    let line = self.fit(format!("{:^1$}", line, width));
+                    write!(f, "{}{}", line, self.line_ending.as_str())?;
+                }
+            }
+        }
+        if !self.year {
+            return write!(f, "{}", self.format(&months));
+        }
+        // each year gets its own banner
+        let width = self.ncol() * self.block_width() + (self.ncol() - 1) * 2;
+        let eol = self.line_ending.as_str();
+        for (i, year) in months.chunks(12).enumerate() {
+            if i > 0 {
+                write!(f, "{0}{0}", eol)?;
+            }
+            for line in self.banner(year[0].year()) {
+                write!(f, "{}{}", self.fit(format!("{:^1$}", line, width)), eol)?;
+            }
+            write!(f, "{}", eol)?;
+            write!(f, "{}", self.format(year))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    fn strip_color(s: &str) -> String {
+        let re = Regex::new(r"\x1b\[\d+m").unwrap();
+        re.replace_all(s, "").to_string()
+    }
+
+    #[test]
+    fn month_year_line_test() {
+        let date = NaiveDate::from_ymd_opt(2022, 1, 1).unwrap();
+        let style = Style::default();
+        assert_eq!(
+            month_year_line(date, false, &style),
+            "    January 2022     "
+        );
+        let date = NaiveDate::from_ymd_opt(2022, 11, 1).unwrap();
+        assert_eq!(
+            month_year_line(date, false, &style),
+            "    November 2022    "
+        );
+        let style = Style {
+            month_number: true,
+            ..Style::default()
+        };
+        assert_eq!(
+            month_year_line(date, false, &style),
+            " November 2022 (11)  "
+        );
+        assert_eq!(month_year_line(date, true, &style), "    November (11)    ");
+    }
+
+    #[test]
+    fn weekday_line_test() {
+        let su = "\x1b[31mSu\x1b[0m Mo Tu We Th Fr \x1b[31mSa\x1b[0m ";
+        assert_eq!(weekday_line(Weekday::Sun, &Style::default()), su);
+        let mo = "Mo Tu We Th Fr \x1b[31mSa\x1b[0m \x1b[31mSu\x1b[0m ";
+        assert_eq!(weekday_line(Weekday::Mon, &Style::default()), mo);
+    }
+
+    #[test]
+    fn weekday_line_case() {
+        let style = |case| Style {
+            weekday_case: case,
+            ..Style::default()
+        };
+        assert_eq!(
+            weekday_line(Weekday::Sun, &style(WeekdayCase::Upper)),
+            "\x1b[31mSU\x1b[0m MO TU WE TH FR \x1b[31mSA\x1b[0m "
+        );
+        assert_eq!(
+            weekday_line(Weekday::Sun, &style(WeekdayCase::Lower)),
+            "\x1b[31msu\x1b[0m mo tu we th fr \x1b[31msa\x1b[0m "
+        );
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, Some(1), (1970, 1, 1))
+            .unwrap()
+            .with_weekday_case(WeekdayCase::Upper);
+        let out = strip_color(&cal.to_string());
+        assert_eq!(out.lines().nth(1).unwrap(), "SU MO TU WE TH FR SA ");
+    }
+
+    #[test]
+    fn day_line_test() {
+        let date = NaiveDate::from_ymd_opt(2022, 11, 1).unwrap();
+        let cur_line = "      \x1b[7m 1\x1b[0m  2  3  4 \x1b[31m 5\x1b[0m ";
+        assert_eq!(
+            day_line(
+                date,
+                Weekday::Sun,
+                11,
+                date,
+                false,
+                &Style::default(),
+                false
+            ),
+            cur_line
+        );
+        let prev_line = "\x1b[31m30\x1b[0m 31                ";
+        assert_eq!(
+            day_line(
+                date,
+                Weekday::Sun,
+                10,
+                date,
+                false,
+                &Style::default(),
+                false
+            ),
+            prev_line
+        );
+    }
+
+    #[test]
+    fn overflow_effects() {
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, None, (1970, 1, 1))
+            .unwrap()
+            .with_overflow_effects(Effect::Dimmed, Effect::Italic);
+        let out = cal.to_string();
+        let lines: Vec<_> = out.lines().collect();
+        assert!(lines[2].contains(" \x1b[2m31\x1b[0m "));
+        assert!(!lines[2].contains("\x1b[3m"));
+        assert!(lines[6].contains("\x1b[3m 1\x1b[0m \x1b[3m 2\x1b[0m"));
+        assert!(!lines[6].contains("\x1b[2m"));
+    }
+
+    #[test]
+    fn highlight_range_endpoints() {
+        let cal = Calendar::new((2022, 11, 1), 2, false, false, 0, Some(2), (1970, 1, 1))
+            .unwrap()
+            .with_weekend_color(false)
+            .with_highlight_range(
+                (2022, 11, 10),
+                (2022, 12, 3),
+                RangeHighlightStyle::default(),
+            )
+            .unwrap();
+        let out = cal.to_string();
+        let lines: Vec<_> = out.lines().collect();
+        assert!(lines[3].starts_with(" 6  7  8  9 \x1b[1m10\x1b[0m \x1b[7m11\x1b[0m "));
+        assert!(lines[6].contains(" \x1b[7m30\x1b[0m "));
+        assert!(lines[2].ends_with(" \x1b[7m 1\x1b[0m \x1b[7m 2\x1b[0m \x1b[1m 3\x1b[0m "));
+        assert!(lines[3].ends_with("  4  5  6  7  8  9 10 "));
+
+        let single = RangeHighlightStyle {
+            endpoints: Effect::Bold,
+            interior: Effect::Underline,
+        };
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, None, (1970, 1, 1))
+            .unwrap()
+            .with_highlight_range((2022, 11, 10), (2022, 11, 10), single)
+            .unwrap();
+        assert!(cal.to_string().contains("\x1b[1m\x1b[4m10\x1b[0m"));
+        assert!(
+            Calendar::new((2022, 11, 1), 1, false, false, 0, None, (1970, 1, 1))
+                .unwrap()
+                .with_highlight_range((2022, 11, 10), (2022, 11, 9), single)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn mark_max_value() {
+        let values = |(_, _, d): (i32, u32, u32)| Some(if d == 15 || d == 20 { 9 } else { 1 });
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_day_values(values)
+            .with_mark_max(true);
+        let out = cal.to_string();
+        assert!(out.contains("14 \x1b[1m15\x1b[0m 16"));
+        assert_eq!(out.matches("\x1b[1m").count(), 1);
+    }
+
+    #[test]
+    fn zebra_weeks() {
+        let plain =
+            Calendar::new((2022, 11, 1), 2, false, false, 0, Some(3), (1970, 1, 1)).unwrap();
+        let cal = Calendar::new((2022, 11, 1), 2, false, false, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_zebra_weeks(true);
+        let out = cal.to_string();
+        assert_eq!(strip_color(&out), strip_color(&plain.to_string()));
+        let shaded: Vec<_> = out.lines().map(|l| l.contains("\x1b[100m")).collect();
+        assert_eq!(
+            shaded,
+            [false, false, false, true, false, true, false, true]
+        );
+    }
+
+    #[test]
+    fn day_cell_radix() {
+        let date = NaiveDate::from_ymd_opt(2022, 10, 31).unwrap();
+        let hex = Style {
+            radix: 16,
+            ..Style::default()
+        };
+        assert_eq!(day_cell(date, &hex), "1F");
+        let date = NaiveDate::from_ymd_opt(2022, 11, 1).unwrap();
+        assert_eq!(day_cell(date, &hex), "01");
+        assert_eq!(day_cell(date, &Style::default()), " 1");
+        let cal = Calendar::new((2022, 10, 1), 1, false, false, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_radix(16)
+            .unwrap();
+        assert!(strip_color(&cal.to_string()).contains("1E 1F 01 02 03 04 05"));
+        assert!(
+            Calendar::new((2022, 10, 1), 1, false, false, 0, None, (1970, 1, 1))
+                .unwrap()
+                .with_radix(5)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn today_line_pinned() {
+        let cal = |fday| {
+            Calendar::new((2022, 11, 1), 1, false, false, fday, Some(1), (2022, 11, 1))
+                .unwrap()
+                .with_today((2022, 11, 11))
+                .unwrap()
+        };
+        assert_eq!(
+            cal(0).today_line(),
+            "Friday, 11 November 2022 — day 315 of 365, week 45"
+        );
+        assert_eq!(
+            cal(1).today_line(),
+            "Friday, 11 November 2022 — day 315 of 365, week 45"
+        );
+        // January 1, 2022 is a Saturday, before the first full week
+        let jan = cal(0).with_today((2022, 1, 1)).unwrap();
+        assert_eq!(
+            jan.today_line(),
+            "Saturday, 1 January 2022 — day 1 of 365, week 0"
+        );
+        let jan = cal(6).with_today((2022, 1, 1)).unwrap();
+        assert!(jan.today_line().ends_with("week 1"));
+    }
+
+    #[test]
+    fn today_header_first() {
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, Some(1), (1970, 1, 1))
+            .unwrap()
+            .with_today((2022, 11, 11))
+            .unwrap()
+            .with_title(Some("Rota".to_string()))
+            .with_today_header(true);
+        let out = strip_color(&cal.to_string());
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines[0], "Today: Friday, 11 November 2022");
+        assert_eq!(lines[1].trim(), "Rota");
+        assert_eq!(lines[2], "    November 2022    ");
+        assert_eq!(lines.len(), 2 + 8);
+    }
+
+    #[test]
+    fn block_banner_rows() {
+        let cal = Calendar::new((2022, 1, 1), 12, false, true, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_block_banner(true);
+        let out = strip_color(&cal.to_string());
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines.len(), cal.dimensions().1);
+        let banner: Vec<_> = lines[..5].iter().map(|l| l.trim()).collect();
+        assert_eq!(
+            banner,
+            [
+                "███ ███ ███ ███",
+                "  █ █ █   █   █",
+                "███ █ █ ███ ███",
+                "█   █ █ █   █  ",
+                "███ ███ ███ ███",
+            ]
+            .map(str::trim)
+        );
+        assert!(lines[..5]
+            .iter()
+            .all(|l| l.chars().count() == lines[6].chars().count()));
+        assert!(lines[5].trim().is_empty());
+        assert_eq!(lines[6].trim_start().split("  ").next(), Some("January"));
+    }
+
+    #[test]
+    fn day_align_sides() {
+        let cal = |align| {
+            let cal = Calendar::new((2022, 11, 1), 2, false, false, 0, Some(2), (1970, 1, 1))
+                .unwrap()
+                .with_day_align(align);
+            strip_color(&cal.to_string())
+        };
+        let right = cal(DayAlign::Right);
+        let left = cal(DayAlign::Left);
+        assert_eq!(
+            &right.lines().nth(2).unwrap()[..21],
+            "       1  2  3  4  5 "
+        );
+        assert_eq!(&left.lines().nth(2).unwrap()[..21], "      1  2  3  4  5  ");
+        assert_eq!(&left.lines().nth(3).unwrap()[..21], "6  7  8  9  10 11 12 ");
+        assert_eq!(&left.lines().nth(6).unwrap()[..21], "27 28 29 30          ");
+    }
+
+    #[test]
+    fn no_weekend_color() {
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, Some(3), (2022, 11, 12))
+            .unwrap()
+            .with_weekend_color(false);
+        let out = cal.to_string();
+        assert!(!out.contains("\x1b[31m"));
+        assert!(out.contains(" 11 \x1b[7m12\x1b[0m "));
+    }
+
+    #[test]
+    fn custom_day_text() {
+        let letter = |_, _, _, w: Weekday| w.to_string()[..1].to_string();
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_day_text(letter);
+        let out = strip_color(&cal.to_string());
+        let lines: Vec<_> = out.lines().collect();
+        assert!(lines[2..].iter().all(|l| *l == " S  M  T  W  T  F  S "));
+        let long = |y: i32, _, _, _| y.to_string();
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_day_text(long);
+        assert_eq!(
+            strip_color(&cal.to_string()).lines().nth(3).unwrap(),
+            "20 20 20 20 20 20 20 "
+        );
+    }
+
+    #[test]
+    fn highlight_if_prime() {
+        let prime = |_, m, d: u32, _| m == 11 && d > 1 && (2..d).all(|k| !d.is_multiple_of(k));
+        let cal = Calendar::new((2022, 11, 1), 2, false, false, 0, Some(2), (1970, 1, 1))
+            .unwrap()
+            .with_weekend_color(false)
+            .with_highlight_if(prime, Effect::Underline);
+        let out = cal.to_string();
+        for d in [2, 3, 5, 7, 11, 13, 17, 19, 23, 29] {
+            assert!(out.contains(&format!("\x1b[4m{:>2}\x1b[0m", d)), "{}", d);
+        }
+        for d in [1, 4, 9, 15, 25, 30] {
+            assert!(!out.contains(&format!("\x1b[4m{:>2}\x1b[0m", d)), "{}", d);
+        }
+        // December days are not prime by the predicate
+        assert_eq!(out.matches("\x1b[4m").count(), 10);
+    }
+
+    #[test]
+    fn day_effects_from_file() {
+        let text = "2022-11-02,bold\n\n2022-11-03, underline\n2022-11-09\n";
+        let effects = parse_day_effects(text).unwrap();
+        assert_eq!(effects.len(), 3);
+        assert_eq!(effects[&(2022, 11, 9)], Effect::Reversed);
+        let cal = Calendar::new((2022, 11, 1), 2, false, false, 0, Some(2), (1970, 1, 1))
+            .unwrap()
+            .with_weekend_color(false)
+            .with_day_effects(&effects)
+            .unwrap();
+        let out = cal.to_string();
+        assert!(out.contains("\x1b[1m 2\x1b[0m"));
+        assert!(out.contains("\x1b[4m 3\x1b[0m"));
+        assert!(out.contains("\x1b[7m 9\x1b[0m"));
+        assert!(out.contains(" 4 "));
+        let config = cal.to_config_string();
+        assert!(config
+            .ends_with(";day-effects=2022-11-02:bold,2022-11-03:underline,2022-11-09:reverse"));
+        let copy = Calendar::from_config_string(&config).unwrap();
+        assert_eq!(copy.to_string(), out);
+        assert_eq!(
+            parse_day_effects("2022-11-02\n2022-11-03,blink"),
+            Err(
+                "line 2: expected plain, dim, italic, bold, underline or reverse, got `blink`"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            parse_day_effects("2022-11-31"),
+            Err("line 1: invalid date `2022-11-31`".to_string())
+        );
+    }
+
+    #[test]
+    fn checkbox_fits_terminal() {
+        // without a terminal, the width is taken to be 80 columns
+        let cal = |nmon, year| {
+            Calendar::new((2022, 11, 1), nmon, nmon == 3, year, 0, None, (1970, 1, 1))
+                .unwrap()
+                .with_checkbox(true)
+        };
+        for cal in [cal(12, true), cal(3, false)] {
+            let out = strip_color(&cal.to_string());
+            assert!(out.lines().all(|l| l.chars().count() <= 80));
+            assert_eq!(cal.dimensions().0, 2 * 28 + if cal.year { 2 } else { 1 });
+        }
+    }
+
+    #[test]
+    fn weekday_names_fit_terminal() {
+        let names = locale_weekday_names("fr_FR.UTF-8").unwrap();
+        for (nmon, year) in [(12, true), (3, false)] {
+            let cal = Calendar::new((2022, 11, 1), nmon, nmon == 3, year, 1, None, (1970, 1, 1))
+                .unwrap()
+                .with_weekday_names(names);
+            let out = strip_color(&cal.to_string());
+            assert!(out.lines().all(|l| l.chars().count() <= 80));
+            assert!(out
+                .lines()
+                .nth(if year { 3 } else { 1 })
+                .unwrap()
+                .starts_with("lun mar"));
+        }
+    }
+
+    #[test]
+    fn checkbox_days() {
+        let done = |(_, _, d): (i32, u32, u32)| Some(if d % 10 == 1 { 1 } else { 0 });
+        let cal = Calendar::new((2022, 11, 1), 2, false, false, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_day_values(done)
+            .with_checkbox(true);
+        let out = strip_color(&cal.to_string());
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(
+            lines[0],
+            format!("{:^28} {:^28}", "November 2022", "December 2022")
+        );
+        assert_eq!(&lines[1][..28], " Su  Mo  Tu  We  Th  Fr  Sa ");
+        assert_eq!(&lines[2][..28], "        [x] [ ] [ ] [ ] [ ] ");
+        assert_eq!(&lines[3][..28], "[ ] [ ] [ ] [ ] [ ] [x] [ ] ");
+        assert!(lines.iter().all(|l| l.chars().count() == 57));
+    }
+
+    #[test]
+    fn locale_weekday_widths() {
+        let names = locale_weekday_names("es_ES.UTF-8").unwrap();
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 1, None, (1970, 1, 1))
+            .unwrap()
+            .with_weekday_names(names);
+        let out = strip_color(&cal.to_string());
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines[0], "       November 2022        ");
+        assert_eq!(lines[1], "lun mar mié jue vie sáb dom ");
+        assert_eq!(lines[2], " 31   1   2   3   4   5   6 ");
+        assert!(lines.iter().all(|l| l.chars().count() == 28));
+        assert_eq!(locale_weekday_names("en_US"), None);
+    }
+
+    #[test]
+    fn calendar_vec() {
+        let date = NaiveDate::from_ymd_opt(2022, 11, 11).unwrap();
+        let cal: Vec<_> =
+            calendar(date, Weekday::Sun, false, date, false, &Style::default()).collect();
+        assert_eq!(
+            cal,
+            [
+                "    November 2022    ",
+                "\x1b[31mSu\x1b[0m Mo Tu We Th Fr \x1b[31mSa\x1b[0m ",
+                "       1  2  3  4 \x1b[31m 5\x1b[0m ",
+                "\x1b[31m 6\x1b[0m  7  8  9 10 \x1b[7m11\x1b[0m \x1b[31m12\x1b[0m ",
+                "\x1b[31m13\x1b[0m 14 15 16 17 18 \x1b[31m19\x1b[0m ",
+                "\x1b[31m20\x1b[0m 21 22 23 24 25 \x1b[31m26\x1b[0m ",
+                "\x1b[31m27\x1b[0m 28 29 30          ",
+                "                     "
+            ]
+        );
+    }
+
+    #[test]
+    fn year_month_blocks() {
+        let blocks = Year::new(2022).unwrap().month_blocks(Weekday::Sun);
+        assert!(blocks.iter().all(|b| b.len() == MONTH_ROWS));
+        assert_eq!(blocks[0][0], "       January       ");
+        assert_eq!(strip_color(&blocks[10][2]), "       1  2  3  4  5 ");
+        let cal = Calendar::new((2022, 1, 1), 12, false, true, 0, Some(1), (1970, 1, 1)).unwrap();
+        let out = strip_color(&cal.to_string());
+        let joined = blocks.iter().flatten().map(|l| strip_color(l)).join("\n");
+        assert!(out.ends_with(&joined));
+    }
+
+    #[test]
+    fn iso_week_list() {
+        let cal = Calendar::new((2023, 1, 1), 1, false, false, 0, None, (1970, 1, 1)).unwrap();
+        let list = cal.to_iso_week_list();
+        let lines: Vec<_> = list.lines().collect();
+        assert_eq!(lines.len(), 31);
+        assert_eq!(lines[..3], ["2022-W52-7", "2023-W01-1", "2023-W01-2"]);
+        assert_eq!(lines[30], "2023-W05-2");
+    }
+
+    #[test]
+    fn moon_illumination_phases() {
+        // full moon on 2022-11-08 at 11:02 UTC, new moon on 2022-11-23
+        assert!(moon_illumination((2022, 11, 8)).unwrap() > 0.98);
+        assert!(moon_illumination((2022, 11, 23)).unwrap() < 0.02);
+        assert!(moon_illumination((1999, 12, 22)).unwrap() > 0.98);
+        assert_eq!(moon_illumination((2022, 2, 30)), None);
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, None, (1970, 1, 1)).unwrap();
+        let list = cal.to_moon_list();
+        assert_eq!(list.lines().count(), 30);
+        assert_eq!(list.lines().nth(7), Some("2022-11-08 100%"));
+    }
+
+    #[test]
+    fn read_date_from_input() {
+        assert_eq!(
+            read_date("2022-11\n".as_bytes()),
+            Some((2022, Some(11), None))
+        );
+        assert_eq!(read_date("2022".as_bytes()), Some((2022, None, None)));
+        assert_eq!(
+            read_date(" 2022-11-11 \nmore".as_bytes()),
+            Some((2022, Some(11), Some(11)))
+        );
+        assert_eq!(read_date("2022-13".as_bytes()), None);
+        assert_eq!(read_date("".as_bytes()), None);
+        let (y, m, _) = read_date("2022-11\n".as_bytes()).unwrap();
+        let ymd = (y, m.unwrap(), 1);
+        let cal = Calendar::new(ymd, 1, false, false, 0, Some(1), ymd).unwrap();
+        let out = strip_color(&cal.to_string());
+        assert_eq!(out.lines().next(), Some("    November 2022    "));
+    }
+
+    #[test]
+    fn first_day_of_locales() {
+        assert_eq!(locale_first_day("de"), 1);
+        assert_eq!(locale_first_day("de_DE.UTF-8"), 1);
+        assert_eq!(locale_first_day("en-US"), 0);
+        assert_eq!(locale_first_day("en_GB.UTF-8"), 1);
+        assert_eq!(locale_first_day("C"), 0);
+        assert_eq!(locale_first_day("ar_EG"), 6);
+    }
+
+    #[test]
+    fn weekday_header_alone() {
+        let names = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+        assert_eq!(
+            strip_color(&weekday_header(1, Some(names)).unwrap()),
+            "Mon Tue Wed Thu Fri Sat Sun "
+        );
+        assert_eq!(
+            strip_color(&weekday_header(0, None).unwrap()),
+            "Su Mo Tu We Th Fr Sa "
+        );
+        assert_eq!(weekday_header(7, None), None);
+    }
+
+    #[test]
+    fn parse_date_validates() {
+        assert_eq!(parse_date("2020-02-29"), Some((2020, 2, 29)));
+        assert_eq!(parse_date("2021-02-29"), None);
+        assert_eq!(parse_date("2022-13-01"), None);
+        assert_eq!(parse_date("2022-11"), None);
+        assert_eq!(parse_date("10000-01-01"), Some((10000, 1, 1)));
+        assert_eq!(parse_date("-44-03-15"), Some((-44, 3, 15)));
+        assert_eq!(parse_date("2022-11-x"), None);
+    }
+
+    #[test]
+    fn date_info_line() {
+        assert_eq!(
+            date_info((2022, 11, 11)).unwrap(),
+            "2022-11-11 is day 315 of 365, ISO 2022-W45-5"
+        );
+        assert_eq!(
+            date_info((2021, 1, 1)).unwrap(),
+            "2021-01-01 is day 1 of 365, ISO 2020-W53-5"
+        );
+        assert_eq!(date_info((2022, 2, 29)), None);
+    }
+
+    #[test]
+    fn year_progress_of_dates() {
+        let progress = year_progress((2022, 11, 11)).unwrap();
+        assert_eq!(format!("{:.1}", progress * 100.0), "86.3");
+        assert!(year_progress((2022, 1, 1)).unwrap() < 0.01);
+        assert_eq!(year_progress((2022, 12, 31)), Some(1.0));
+        assert_eq!(year_progress((2020, 12, 31)), Some(1.0));
+        assert_eq!(year_progress((2022, 2, 29)), None);
+    }
+
+    #[test]
+    fn date_matching_across_years() {
+        assert_eq!(next_date_matching((2021, 3, 1), 2, 29), Some((2024, 2, 29)));
+        assert_eq!(
+            next_date_matching((2024, 2, 29), 2, 29),
+            Some((2028, 2, 29))
+        );
+        assert_eq!(next_date_matching((1896, 3, 1), 2, 29), Some((1904, 2, 29)));
+        assert_eq!(
+            next_date_matching((2022, 11, 11), 12, 25),
+            Some((2022, 12, 25))
+        );
+        assert_eq!(prev_date_matching((2021, 3, 1), 2, 29), Some((2020, 2, 29)));
+        assert_eq!(
+            prev_date_matching((2022, 11, 11), 12, 25),
+            Some((2021, 12, 25))
+        );
+        assert_eq!(next_date_matching((2022, 11, 11), 2, 30), None);
+    }
+
+    #[test]
+    fn add_business_days_skips() {
+        let weekend = [Weekday::Sat, Weekday::Sun];
+        assert_eq!(
+            add_business_days((2022, 11, 18), 5, &weekend, &[]),
+            Some((2022, 11, 25))
+        );
+        let thanksgiving = [(2022, 11, 24)];
+        assert_eq!(
+            add_business_days((2022, 11, 18), 5, &weekend, &thanksgiving),
+            Some((2022, 11, 28))
+        );
+        assert_eq!(
+            add_business_days((2022, 11, 19), 0, &weekend, &[]),
+            Some((2022, 11, 19))
+        );
+        assert_eq!(
+            add_business_days((2022, 12, 30), 1, &weekend, &[]),
+            Some((2023, 1, 2))
+        );
+    }
+
+    #[test]
+    fn nearest_weekend_ties() {
+        let weekend = [Weekday::Sat, Weekday::Sun];
+        // Wednesday, three days from both Sunday and Saturday
+        assert_eq!(
+            nearest_weekend((2022, 11, 16), &weekend),
+            Some((2022, 11, 19))
+        );
+        // Monday, a day after Sunday
+        assert_eq!(
+            nearest_weekend((2022, 11, 14), &weekend),
+            Some((2022, 11, 13))
+        );
+        assert_eq!(
+            nearest_weekend((2022, 11, 13), &weekend),
+            Some((2022, 11, 13))
+        );
+        let friday = [Weekday::Fri];
+        assert_eq!(
+            nearest_weekend((2022, 11, 15), &friday),
+            Some((2022, 11, 18))
+        );
+        assert_eq!(nearest_weekend((2022, 11, 14), &[]), None);
+    }
+
+    #[test]
+    fn week_view_across_months() {
+        let week = week_view((2022, 11, 30), 0, LineEnding::Lf).unwrap();
+        assert_eq!(
+            strip_color(&week),
+            "\
+\x2027 Nov - 3 Dec 2022 \n\
+             Su Mo Tu We Th Fr Sa \n\
+             27 28 29 30  1  2  3 "
+        );
+        assert!(week.contains("\x1b[7m30\x1b[0m"));
+    }
+
+    #[test]
+    fn weeks_view_from_today() {
+        let weeks = weeks_view((2022, 11, 11), 0, 2, LineEnding::Lf).unwrap();
+        assert_eq!(
+            strip_color(&weeks),
+            "\
+\x206 Nov - 19 Nov 2022 \n\
+             Su Mo Tu We Th Fr Sa \n\
+\x206  7  8  9 10 11 12 \n\
+             13 14 15 16 17 18 19 "
+        );
+        assert!(weeks.contains("\x1b[7m11\x1b[0m"));
+        assert!(weeks_view((262143, 12, 1), 0, 10000, LineEnding::Lf).is_none());
+    }
+
+    #[test]
+    fn iso_week_strip() {
+        let week = iso_week_view(2022, 45, LineEnding::Lf).unwrap();
+        assert_eq!(
+            strip_color(&week),
+            "\
+\x20     2022-W45       \n\
+             Mo Tu We Th Fr Sa Su \n\
+\x207  8  9 10 11 12 13 "
+        );
+        // across months, and years
+        let week = strip_color(&iso_week_view(2022, 52, LineEnding::Lf).unwrap());
+        assert_eq!(week.lines().nth(2), Some("26 27 28 29 30 31  1 "));
+        let week = strip_color(&iso_week_view(2021, 1, LineEnding::Lf).unwrap());
+        assert_eq!(week.lines().nth(2), Some(" 4  5  6  7  8  9 10 "));
+        assert_eq!(iso_week_view(2022, 53, LineEnding::Lf), None);
+        assert!(iso_week_view(2020, 53, LineEnding::Lf).is_some());
+        let crlf = iso_week_view(2022, 45, LineEnding::Crlf).unwrap();
+        assert_eq!(crlf.matches("\r\n").count(), 2);
+        let lf = iso_week_view(2022, 45, LineEnding::Lf).unwrap();
+        assert_eq!(crlf.replace("\r\n", "\n"), lf);
+    }
+
+    #[test]
+    fn iso_weeks_of_range() {
+        // 2022-W44 to 2022-W52, then 2023-W01 to 2023-W05
+        let cal = Calendar::new((2022, 12, 1), 3, true, false, 0, None, (1970, 1, 1)).unwrap();
+        assert_eq!(cal.iso_week_count(), 14);
+        // a week shared by two months is counted once
+        let cal = Calendar::new((2022, 11, 1), 2, false, false, 0, None, (1970, 1, 1)).unwrap();
+        assert_eq!(cal.iso_week_count(), 9);
+    }
+
+    #[test]
+    fn strip_across_months() {
+        let cal = Calendar::new((2022, 11, 1), 2, false, false, 0, None, (1970, 1, 1)).unwrap();
+        let strip = cal.to_strip(40);
+        assert!(strip.starts_with("Nov  1  2  3"));
+        assert!(strip.lines().all(|l| l.len() <= 40));
+        let flat = strip.lines().join(" ");
+        assert!(flat.contains("29 30 | Dec  1  2"));
+        assert!(flat.ends_with("30 31"));
+        assert_eq!(flat.matches('|').count(), 1);
+    }
+
+    #[test]
+    fn cells_metadata() {
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, None, (2022, 11, 11))
+            .unwrap()
+            .with_today((2022, 11, 11))
+            .unwrap();
+        let cells = cal.cells();
+        assert_eq!(cells.len(), 42);
+        assert_eq!(cells.iter().filter(|c| c.day.is_some()).count(), 30);
+        assert_eq!(cells[0].day, None);
+        assert_eq!(cells[2].day, Some(1));
+        let today: Vec<_> = cells.iter().filter(|c| c.is_today).collect();
+        assert_eq!(
+            today,
+            [&Cell {
+                month: MonthOfYear::new(2022, 11).unwrap(),
+                day: Some(11),
+                adjacent: None,
+                weekday: Weekday::Fri,
+                is_weekend: false,
+                is_today: true,
+                is_highlight: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn cells_adjacent_days() {
+        let cal = |nmon| {
+            Calendar::new((2022, 11, 1), nmon, false, false, 0, Some(3), (1970, 1, 1)).unwrap()
         };
-        itertools::iterate(start, |d| *d + Months::new(1)).take(self.nmon as usize)
+        let cells = cal(1).cells();
+        let oct = MonthOfYear::new(2022, 10).unwrap();
+        let first_week: Vec<_> = cells[..7].iter().map(|c| (c.day, c.adjacent)).collect();
+        assert_eq!(
+            first_week,
+            [
+                (None, Some((oct, 30))),
+                (None, Some((oct, 31))),
+                (Some(1), None),
+                (Some(2), None),
+                (Some(3), None),
+                (Some(4), None),
+                (Some(5), None),
+            ]
+        );
+        let dec = MonthOfYear::new(2022, 12).unwrap();
+        assert_eq!(cells[41].adjacent, Some((dec, 10)));
+        // padding of several months stays blank, as it renders
+        assert!(cal(2).cells().iter().all(|c| c.adjacent.is_none()));
+        let cells = cal(1).with_adjacent_days(false).cells();
+        assert!(cells.iter().all(|c| c.adjacent.is_none()));
     }
 
-    fn format(&self) -> String {
-        self.iter_month()
-            .map(|m| calendar(m, self.fday, self.year, self.hlight, self.nmon == 1))
-            .collect_vec()
-            .chunks_mut(self.ncol)
-            .flat_map(|vec_of_iters| {
-                (0..MONTH_ROWS).map(|_| {
-                    vec_of_iters
-                        .iter_mut()
-                        .map(|it| it.next().unwrap())
-                        .join(if self.year { "  " } else { " " })
-                })
-            })
-            .join("\n")
+    #[test]
+    fn day_count_months() {
+        let cal = |ymd, nmon| Calendar::new(ymd, nmon, false, false, 0, Some(3), ymd).unwrap();
+        assert_eq!(cal((2020, 2, 1), 1).day_count(), 29);
+        assert_eq!(cal((2021, 2, 1), 1).day_count(), 28);
+        assert_eq!(cal((2022, 11, 1), 3).day_count(), 30 + 31 + 31);
     }
-}
 
-impl std::fmt::Display for Calendar {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.year {
-            let width = self.ncol * MONTH_WIDTH + (self.ncol - 1) * 2;
-            write!(f, "{:^1$}\n\n", self.query.year(), width)?;
-        }
-        write!(f, "{}", self.format())
+    #[test]
+    fn days_marked_at_boundaries() {
+        let cal = Calendar::new((2022, 11, 1), 2, false, false, 0, Some(2), (1970, 1, 1)).unwrap();
+        let days = cal.iter_days_marked().collect_vec();
+        assert_eq!(days.len(), 61);
+        let date = |m, d| NaiveDate::from_ymd_opt(2022, m, d).unwrap();
+        assert_eq!(days[0], (date(11, 1), true, false));
+        assert_eq!(days[1], (date(11, 2), false, false));
+        assert_eq!(days[29], (date(11, 30), false, true));
+        assert_eq!(days[30], (date(12, 1), true, false));
+        assert_eq!(days[60], (date(12, 31), false, true));
+        assert_eq!(days.iter().filter(|d| d.1).count(), 2);
+        assert_eq!(days.iter().filter(|d| d.2).count(), 2);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use regex::Regex;
+    #[test]
+    fn cell_stats_counts() {
+        let cal = |nmon| {
+            Calendar::new((2022, 11, 1), nmon, false, false, 0, Some(3), (1970, 1, 1)).unwrap()
+        };
+        assert_eq!(cal(1).cell_stats(), (42, 30));
+        assert_eq!(cal(3).cell_stats(), (126, 30 + 31 + 31));
+    }
 
-    fn strip_color(s: &str) -> String {
-        let re = Regex::new(r"\x1b\[\d+m").unwrap();
-        re.replace_all(&s, "").to_string()
+    #[test]
+    fn planner_corners() {
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, Some(1), (1970, 1, 1)).unwrap();
+        let out = cal.to_planner(PlannerStyle::default());
+        let lines: Vec<_> = out.lines().collect();
+        // title, top, weekdays, then five weeks of a rule and four lines
+        assert_eq!(lines.len(), 3 + 5 * 5 + 1);
+        assert_eq!(lines[0].trim(), "November 2022");
+        assert_eq!(lines[1], format!("┌{}┐", vec!["─".repeat(10); 7].join("┬")));
+        assert!(lines[2].starts_with("│Su        │Mo        │"));
+        let blank = format!("│{}│", vec![" ".repeat(10); 7].join("│"));
+        assert_eq!(
+            lines[4],
+            "│          │          │1         │2         │3         │4         │5         │"
+        );
+        assert!(lines[5..8].iter().all(|l| *l == blank));
+        assert!(lines[24].starts_with("│27        │28        │29        │30        │   "));
+        assert_eq!(lines[27], blank);
+        assert_eq!(
+            lines[28],
+            format!("└{}┘", vec!["─".repeat(10); 7].join("┴"))
+        );
+        let small = cal.to_planner(PlannerStyle {
+            width: 3,
+            height: 1,
+        });
+        assert_eq!(
+            small.lines().nth(4).unwrap(),
+            "│   │   │1  │2  │3  │4  │5  │"
+        );
     }
 
     #[test]
-    fn month_year_line_test() {
-        let date = NaiveDate::from_ymd_opt(2022, 1, 1).unwrap();
-        assert_eq!(month_year_line(date, false), "    January 2022     ");
-        let date = NaiveDate::from_ymd_opt(2022, 11, 1).unwrap();
-        assert_eq!(month_year_line(date, false), "    November 2022    ");
+    fn grid_shape() {
+        let cal = Calendar::new((2022, 11, 1), 2, false, false, 0, Some(1), (1970, 1, 1)).unwrap();
+        let grid = cal.grid();
+        assert_eq!(grid.len(), 2);
+        assert!(grid.iter().all(|row| row.len() == 6));
+        assert!(grid.iter().flatten().all(|week| week.len() == 7));
+        assert_eq!(
+            grid[0][0],
+            [None, None, Some(1), Some(2), Some(3), Some(4), Some(5)]
+        );
+        assert_eq!(grid[1][4][6], Some(31));
+
+        let cal = Calendar::new((2022, 11, 1), 2, false, false, 0, Some(2), (1970, 1, 1)).unwrap();
+        assert_eq!(cal.grid().len(), 1);
+        assert_eq!(cal.grid()[0][0].len(), 14);
     }
 
     #[test]
-    fn weekday_line_test() {
-        let su = "\x1b[31mSu\x1b[0m Mo Tu We Th Fr \x1b[31mSa\x1b[0m ";
-        assert_eq!(weekday_line(Weekday::Sun), su);
-        let mo = "Mo Tu We Th Fr \x1b[31mSa\x1b[0m \x1b[31mSu\x1b[0m ";
-        assert_eq!(weekday_line(Weekday::Mon), mo);
+    fn truncate_narrow_width() {
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, None, (2022, 11, 11))
+            .unwrap()
+            .with_max_width(Some(10));
+        let out = cal.to_string();
+        let lines = strip_color(&out);
+        let lines: Vec<_> = lines.lines().collect();
+        assert_eq!(lines[1], "Su Mo Tu …");
+        assert_eq!(lines[3], " 6  7  8 …");
+        assert!(lines.iter().all(|l| l.chars().count() == 10));
+        assert_eq!(truncate("\x1b[31mabc\x1b[0m", 3), "\x1b[31mabc\x1b[0m");
+        assert_eq!(truncate("\x1b[31mabcd\x1b[0m", 3), "\x1b[31mab\x1b[0m…");
     }
 
     #[test]
-    fn day_line_test() {
-        let date = NaiveDate::from_ymd_opt(2022, 11, 1).unwrap();
-        let cur_line = "      \x1b[7m 1\x1b[0m  2  3  4 \x1b[31m 5\x1b[0m ";
-        assert_eq!(day_line(date, Weekday::Sun, 11, date, false), cur_line);
-        let prev_line = "\x1b[31m30\x1b[0m 31                ";
-        assert_eq!(day_line(date, Weekday::Sun, 10, date, false), prev_line);
+    fn days_until_target() {
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, None, (1970, 1, 1))
+            .unwrap()
+            .with_today((2022, 11, 20))
+            .unwrap();
+        assert_eq!(cal.days_until((2023, 1, 1)), Some(42));
+        assert_eq!(cal.days_until((2022, 11, 20)), Some(0));
+        assert_eq!(cal.days_until((2022, 11, 11)), Some(-9));
+        assert_eq!(cal.days_until((2022, 2, 30)), None);
     }
 
     #[test]
-    fn calendar_vec() {
-        let date = NaiveDate::from_ymd_opt(2022, 11, 11).unwrap();
-        let cal: Vec<_> = calendar(date, Weekday::Sun, false, date, false).collect();
+    fn heatmap_of_year() {
+        let cal = Calendar::new((2022, 1, 1), 1, false, false, 0, None, (1970, 1, 1)).unwrap();
+        let values = |(_, m, d): (i32, u32, u32)| Some(if m == 11 { d as i64 } else { 0 });
+        let map = strip_color(&cal.to_heatmap(2022, &values).unwrap());
+        let rows: Vec<_> = map.lines().collect();
+        assert_eq!(rows.len(), 7);
+        assert!(rows.iter().all(|r| r.chars().count() == 53));
+        let blocks = map.chars().filter(|c| !c.is_whitespace()).count();
+        assert_eq!(blocks, 365);
+        assert_eq!(map.matches('·').count(), 335);
+        assert_eq!(map.matches('█').count(), 8);
+        // 2022 starts on a Saturday, so only the last row starts with a day
+        assert!(rows[..6].iter().all(|r| r.starts_with(' ')));
+        assert!(rows[6].starts_with('·'));
+        assert_eq!(cal.to_heatmap(2020, &values).unwrap().lines().count(), 7);
+    }
+
+    #[test]
+    fn supported_years() {
+        let cal = |y| Calendar::new((y, 1, 1), 1, false, false, 0, None, (1970, 1, 1)).unwrap();
+        assert!(cal(0).with_supported_years(1..=9999).is_none());
+        assert!(cal(100000).with_supported_years(1..=9999).is_none());
+        assert!(cal(2022).with_supported_years(1..=9999).is_some());
+        assert!(cal(9999)
+            .with_years(2)
+            .unwrap()
+            .with_supported_years(1..=9999)
+            .is_none());
+        // the window holds for the months chosen after it
+        let window = cal(9999).with_supported_years(1..=9999).unwrap();
+        let config = window.to_config_string();
+        assert!(config.ends_with(";supported-years=1..9999"));
+        let window = Calendar::from_config_string(&config).unwrap();
+        assert!(window.with_years(2).is_none());
+        let window = cal(1).with_supported_years(1..=9999).unwrap();
+        assert!(window.with_academic_year(9).is_none());
+    }
+
+    #[test]
+    fn config_string_round_trip() {
+        let cal = Calendar::new((2022, 11, 1), 3, true, false, 1, Some(2), (2022, 11, 11))
+            .unwrap()
+            .with_flip(true)
+            .with_radix(16)
+            .unwrap()
+            .with_today_prefix(Some(';'))
+            .with_overflow_effects(Effect::Plain, Effect::Italic)
+            .with_week_epoch((2022, 9, 1))
+            .unwrap()
+            .with_last_row_align(Align::Center)
+            .with_highlight_range(
+                (2022, 11, 10),
+                (2022, 12, 3),
+                RangeHighlightStyle {
+                    endpoints: Effect::Underline,
+                    interior: Effect::Plain,
+                },
+            )
+            .unwrap();
+        let config = cal.to_config_string();
+        assert!(config.starts_with(
+            "date=2022-11-01;months=3;first=1;columns=2;highlight=2022-11-11;span;flip;"
+        ));
+        let copy = Calendar::from_config_string(&config).unwrap();
+        assert_eq!(copy.to_config_string(), config);
+        assert_eq!(copy.to_string(), cal.to_string());
+        assert!(Calendar::from_config_string("date=2022-11-01;months=3").is_none());
+        assert!(config.contains(";range=2022-11-10..2022-12-03;range-ends=underline;"));
+        assert!(Calendar::from_config_string(&config.replace("center", "up")).is_none());
+        assert!(Calendar::from_config_string(&config.replace("..", "-")).is_none());
+    }
+
+    #[test]
+    fn split_output_files() {
+        let jan = MonthOfYear::new(2023, 1).unwrap();
         assert_eq!(
-            cal,
-            [
-                "    November 2022    ",
-                "\x1b[31mSu\x1b[0m Mo Tu We Th Fr \x1b[31mSa\x1b[0m ",
-                "       1  2  3  4 \x1b[31m 5\x1b[0m ",
-                "\x1b[31m 6\x1b[0m  7  8  9 10 \x1b[7m11\x1b[0m \x1b[31m12\x1b[0m ",
-                "\x1b[31m13\x1b[0m 14 15 16 17 18 \x1b[31m19\x1b[0m ",
-                "\x1b[31m20\x1b[0m 21 22 23 24 25 \x1b[31m26\x1b[0m ",
-                "\x1b[31m27\x1b[0m 28 29 30          ",
-                "                     "
-            ]
+            fill_pattern("cal-{year}-{month:02}.txt", jan),
+            "cal-2023-01.txt"
+        );
+        assert_eq!(fill_pattern("{month}/{year}", jan), "1/2023");
+
+        let dir = std::env::temp_dir().join(format!("carender-split-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pattern = dir.join("cal-{year}-{month:02}.txt");
+        let cal = Calendar::new((2022, 12, 1), 2, false, false, 0, Some(3), (1970, 1, 1)).unwrap();
+        let results = cal.write_split(pattern.to_str().unwrap());
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|(_, r)| r.is_ok()));
+        let dec = std::fs::read_to_string(dir.join("cal-2022-12.txt")).unwrap();
+        let jan = std::fs::read_to_string(dir.join("cal-2023-01.txt")).unwrap();
+        assert!(strip_color(&dec).starts_with("    December 2022    \n"));
+        assert!(strip_color(&jan).starts_with("    January 2023     \n"));
+        assert_eq!(jan.lines().count(), MONTH_ROWS);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let missing = dir.join("missing").join("{month}.txt");
+        let results = cal.write_split(missing.to_str().unwrap());
+        assert!(results.iter().all(|(_, r)| r.is_err()));
+    }
+
+    #[test]
+    fn csv_rows() {
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, None, (1970, 1, 1))
+            .unwrap()
+            .with_today((2022, 11, 11))
+            .unwrap();
+        let csv = cal.to_csv();
+        let rows: Vec<_> = csv.split_terminator("\r\n").collect();
+        assert_eq!(rows.len(), 31);
+        assert_eq!(rows[0], "year,month,day,weekday,is_weekend,is_today");
+        assert_eq!(rows[11], "2022,11,11,Fri,false,true");
+        assert_eq!(rows[12], "2022,11,12,Sat,true,false");
+        assert_eq!(csv_field("a,\"b\""), "\"a,\"\"b\"\"\"");
+    }
+
+    #[test]
+    fn month_separator() {
+        let cal = Calendar::new((2022, 11, 1), 2, false, false, 0, Some(2), (1970, 1, 1))
+            .unwrap()
+            .with_month_separator('│');
+        let out = strip_color(&cal.to_string());
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines[0], "    November 2022    │    December 2022    ");
+        assert_eq!(lines[2], "       1  2  3  4  5 │             1  2  3 ");
+        assert!(lines.iter().all(|l| l.chars().nth(21) == Some('│')));
+        let cal = Calendar::from_config_string(&cal.to_config_string()).unwrap();
+        assert_eq!(strip_color(&cal.to_string()), out);
+        // the year banner spans the separators
+        let cal = Calendar::new((2022, 1, 1), 12, false, true, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_month_separator('│');
+        let out = strip_color(&cal.to_string());
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines[0].trim(), "2022");
+        assert_eq!(lines[0].chars().count(), lines[2].chars().count());
+        assert_eq!(lines[2].chars().nth(22), Some('│'));
+    }
+
+    #[test]
+    fn weekday_base_first_day() {
+        let header = |base: WeekdayBase, n| {
+            let fday = base.first_day(n).unwrap();
+            let cal = Calendar::new((2022, 11, 1), 1, false, false, fday, Some(1), (1970, 1, 1));
+            strip_color(&cal.unwrap().to_string())
+                .lines()
+                .nth(1)
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(header(WeekdayBase::Sunday0, 0), "Su Mo Tu We Th Fr Sa ");
+        assert_eq!(header(WeekdayBase::Monday0, 0), "Mo Tu We Th Fr Sa Su ");
+        assert_eq!(header(WeekdayBase::Monday0, 6), "Su Mo Tu We Th Fr Sa ");
+        assert_eq!(header(WeekdayBase::Monday1, 1), "Mo Tu We Th Fr Sa Su ");
+        assert_eq!(header(WeekdayBase::Monday1, 7), "Su Mo Tu We Th Fr Sa ");
+        assert_eq!(WeekdayBase::Sunday0.first_day(7), None);
+        assert_eq!(WeekdayBase::Monday1.first_day(0), None);
+    }
+
+    #[test]
+    fn academic_year() {
+        let cal = |ymd| {
+            Calendar::new(ymd, 1, false, false, 0, Some(3), ymd)
+                .unwrap()
+                .with_academic_year(9)
+                .unwrap()
+        };
+        let months = |cal: &Calendar| {
+            cal.month_strings()
+                .into_iter()
+                .map(|(m, _)| m)
+                .collect_vec()
+        };
+        let expected = (0..12)
+            .map(|i| MonthOfYear::from_absolute_index(2022 * 12 + 8 + i).unwrap())
+            .collect_vec();
+        assert_eq!(months(&cal((2022, 11, 11))), expected);
+        assert_eq!(months(&cal((2023, 8, 31))), expected);
+        assert_eq!(months(&cal((2022, 9, 1))), expected);
+        let out = strip_color(&cal((2022, 11, 11)).to_string());
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines[0].trim(), "2022–2023 Academic Year");
+        assert!(lines[1].starts_with("   September 2022   "));
+        assert!(out.contains("August 2023"));
+        let titled = Calendar::new((2022, 11, 11), 1, false, false, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_title(Some("Mine".to_string()))
+            .with_academic_year(9)
+            .unwrap();
+        assert_eq!(
+            strip_color(&titled.to_string())
+                .lines()
+                .next()
+                .unwrap()
+                .trim(),
+            "Mine"
+        );
+        assert!(
+            Calendar::for_date(NaiveDate::from_ymd_opt(2022, 1, 1).unwrap())
+                .with_academic_year(13)
+                .is_none()
         );
     }
 
+    #[test]
+    fn rotate_first_weekday() {
+        let cal =
+            || Calendar::new((2022, 11, 1), 2, false, false, 0, Some(2), (1970, 1, 1)).unwrap();
+        let header =
+            |cal: Calendar| strip_color(&cal.to_string()).lines().nth(1).unwrap()[..21].to_string();
+        assert_eq!(
+            header(cal().rotate_first_weekday(1)),
+            "Mo Tu We Th Fr Sa Su "
+        );
+        assert_eq!(
+            header(cal().rotate_first_weekday(-1)),
+            "Sa Su Mo Tu We Th Fr "
+        );
+        assert_eq!(
+            header(cal().rotate_first_weekday(15)),
+            "Mo Tu We Th Fr Sa Su "
+        );
+        assert_eq!(header(cal().rotate_first_weekday(-7)), header(cal()));
+    }
+
+    #[test]
+    fn csv_export_numbers() {
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, None, (1970, 1, 1))
+            .unwrap()
+            .with_today((2022, 11, 11))
+            .unwrap()
+            .with_export_numbers(true);
+        let csv = cal.to_csv();
+        let rows: Vec<_> = csv.split_terminator("\r\n").collect();
+        assert_eq!(
+            rows[0],
+            "year,month,day,weekday,is_weekend,is_today,iso_week,day_of_year"
+        );
+        assert_eq!(rows[1], "2022,11,1,Tue,false,false,44,305");
+        assert_eq!(rows[11], "2022,11,11,Fri,false,true,45,315");
+        let cal = Calendar::from_config_string(&cal.to_config_string()).unwrap();
+        assert!(cal.to_csv().starts_with(rows[0]));
+    }
+
     #[test]
     fn draw_single_month() {
         let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, Some(3), (1970, 1, 1)).unwrap();
@@ -272,6 +3473,376 @@ mod tests {
         );
     }
 
+    #[test]
+    fn draw_week_dividers() {
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_week_dividers(true)
+            .with_trim(true);
+        assert_eq!(
+            strip_color(&cal.to_string()),
+            "\
+\x20   November 2022    \n\
+             Su Mo Tu We Th Fr Sa \n\
+             30 31  1  2  3  4  5 \n\
+             ──────────────────── \n\
+\x206  7  8  9 10 11 12 \n\
+             ──────────────────── \n\
+             13 14 15 16 17 18 19 \n\
+             ──────────────────── \n\
+             20 21 22 23 24 25 26 \n\
+             ──────────────────── \n\
+             27 28 29 30  1  2  3 "
+        );
+        let cal = Calendar::new((2022, 11, 1), 2, false, false, 0, Some(2), (1970, 1, 1))
+            .unwrap()
+            .with_week_dividers(true);
+        assert_eq!(cal.to_string().lines().count(), 2 + 11);
+    }
+
+    #[test]
+    fn draw_vflipped_month() {
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, Some(3), (1970, 1, 1)).unwrap();
+        let upright = strip_color(&cal.to_string());
+        let cal = cal.with_vflip(true);
+        let flipped = strip_color(&cal.to_string());
+        assert_eq!(flipped.lines().rev().join("\n"), upright);
+        assert_eq!(flipped.lines().last().unwrap(), "    November 2022    ");
+        assert!(flipped.starts_with(" 4  5  6  7  8  9 10 \n27 28 29 30  1  2  3 "));
+    }
+
+    #[test]
+    fn draw_minimal() {
+        let cal = Calendar::new((2022, 11, 1), 2, false, false, 0, Some(2), (1970, 1, 1))
+            .unwrap()
+            .with_today((2022, 11, 11))
+            .unwrap()
+            .with_minimal(true);
+        let out = strip_color(&cal.to_string());
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines[2], "       ·  ·  ·  ·  ·               ·  ·  · ");
+        assert_eq!(lines[3], " ·  ·  ·  ·  · 11  ·   ·  ·  ·  ·  ·  ·  · ");
+        let days = out
+            .lines()
+            .skip(2)
+            .flat_map(|l| l.split_whitespace())
+            .collect_vec();
+        assert_eq!(days.len(), 30 + 31);
+        assert!(days.iter().all(|&d| d == "·" || d == "11"));
+        assert_eq!(days.iter().filter(|&&d| d == "11").count(), 1);
+    }
+
+    #[test]
+    fn draw_full_width() {
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, Some(3), (2022, 11, 11))
+            .unwrap()
+            .with_full_width(true);
+        let out = cal.to_string();
+        assert!(out.contains("\x1b[7m１１\x1b[0m"));
+        let out = strip_color(&out);
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines[0], "　　　　Ｎｏｖｅｍｂｅｒ　２０２２　　　　");
+        assert_eq!(lines[1], "Ｓｕ　Ｍｏ　Ｔｕ　Ｗｅ　Ｔｈ　Ｆｒ　Ｓａ　");
+        // every character is double width, so the block is 42 columns wide
+        assert!(lines.iter().all(|l| l.chars().count() == MONTH_WIDTH));
+        assert!(lines.iter().all(|l| l.chars().all(|c| !c.is_ascii())));
+    }
+
+    #[test]
+    fn full_width_keeps_alignment() {
+        let columns = |l: &str| -> usize {
+            l.chars()
+                .map(|c| match c {
+                    '\u{3000}'..='\u{30ff}' | '\u{ff01}'..='\u{ff5e}' => 2,
+                    _ => 1,
+                })
+                .sum()
+        };
+        let cal = |nmon, year, ncol| {
+            Calendar::new((2022, 11, 1), nmon, false, year, 0, ncol, (2022, 11, 11))
+                .unwrap()
+                .with_full_width(true)
+        };
+        let out = strip_color(&cal(1, false, None).with_week_dividers(true).to_string());
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines[3], format!("{}\u{3000}", "─".repeat(40)));
+        assert!(lines.iter().all(|l| columns(l) == 2 * MONTH_WIDTH));
+        let out = strip_color(&cal(2, false, Some(1)).with_minimal(true).to_string());
+        assert!(out.contains("・"));
+        assert!(out.lines().all(|l| columns(l) == 2 * MONTH_WIDTH));
+        let names = ["dom", "lun", "mar", "mié", "jue", "vie", "sáb"];
+        let out = strip_color(&cal(1, false, None).with_weekday_names(names).to_string());
+        assert!(out.lines().skip(1).all(|l| columns(l) == 2 * 28));
+
+        // without a terminal, the width is taken to be 80 columns
+        let year = cal(12, true, None).with_block_banner(true);
+        let out = strip_color(&year.to_string());
+        assert!(out.lines().all(|l| columns(l) <= 80));
+        assert_eq!(year.dimensions().0, 2 * MONTH_WIDTH);
+        let cal = cal(2, false, Some(2)).with_month_separator('│');
+        let out = strip_color(&cal.to_string());
+        assert!(out.lines().all(|l| columns(l) == 2 * (2 * MONTH_WIDTH + 1)));
+        let out = strip_color(&cal.with_max_width(Some(30)).to_string());
+        assert!(out.lines().all(|l| columns(l) <= 30));
+        assert!(out.lines().any(|l| l.ends_with("… ")));
+    }
+
+    #[test]
+    fn calendar_date_bounds() {
+        let cal = Calendar::new((2022, 11, 11), 1, false, false, 0, None, (1970, 1, 1)).unwrap();
+        assert_eq!(cal.date_bounds(), ((2022, 10, 30), (2022, 12, 10)));
+        let cal = cal.with_trim(true);
+        assert_eq!(cal.date_bounds(), ((2022, 10, 30), (2022, 12, 3)));
+        let cal = cal.with_adjacent_days(false);
+        assert_eq!(cal.date_bounds(), ((2022, 11, 1), (2022, 11, 30)));
+        assert!(!strip_color(&cal.to_string()).contains("30 31"));
+        let cal = Calendar::new((2022, 11, 1), 3, false, false, 0, None, (1970, 1, 1)).unwrap();
+        assert_eq!(cal.date_bounds(), ((2022, 11, 1), (2023, 1, 31)));
+    }
+
+    #[test]
+    fn draw_header_gap() {
+        let cal = Calendar::new((2022, 11, 1), 2, false, false, 0, Some(2), (1970, 1, 1))
+            .unwrap()
+            .with_header_gap(1);
+        let out = strip_color(&cal.to_string());
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines.len(), MONTH_ROWS + 1);
+        assert!(lines[1].starts_with("Su Mo Tu We Th Fr Sa "));
+        assert_eq!(lines[2], " ".repeat(2 * MONTH_WIDTH + 1));
+        assert!(lines[3].starts_with("       1  2  3  4  5 "));
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, Some(1), (1970, 1, 1))
+            .unwrap()
+            .with_header_gap(2)
+            .with_trim(true);
+        assert_eq!(cal.to_string().lines().count(), 2 + 2 + 5);
+    }
+
+    #[test]
+    fn draw_title() {
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_title(Some("Team rota".to_string()));
+        assert_eq!(cal.dimensions(), (MONTH_WIDTH, MONTH_ROWS));
+        let out = strip_color(&cal.to_string());
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines.len(), 1 + MONTH_ROWS);
+        assert_eq!(lines[0], "      Team rota      ");
+        assert_eq!(lines[1], "    November 2022    ");
+        let cal = cal.with_title(Some("Rota for the platform team".to_string()));
+        let out = strip_color(&cal.to_string());
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines[0], "Rota for the platform");
+        assert_eq!(lines[1], "        team         ");
+    }
+
+    #[test]
+    fn calendar_dimensions() {
+        let cal = Calendar::new((2022, 1, 1), 12, false, true, 0, Some(3), (1970, 1, 1)).unwrap();
+        let out = cal.to_string();
+        assert_eq!(cal.dimensions(), (3 * MONTH_WIDTH + 4, out.lines().count()));
+        let cal = cal.with_years(2).unwrap();
+        assert_eq!(cal.dimensions().1, cal.to_string().lines().count());
+        let cal = Calendar::new((2022, 1, 1), 2, false, false, 0, Some(3), (1970, 1, 1)).unwrap();
+        assert_eq!(cal.dimensions(), (2 * MONTH_WIDTH + 1, MONTH_ROWS));
+
+        // a year view of fewer than twelve months, as config strings allow
+        let cal = Calendar::new((2022, 1, 1), 1, false, true, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_title(Some("Plan".to_string()));
+        let out = cal.to_string();
+        assert_eq!(cal.dimensions().1 + 1, out.lines().count());
+        let config = "date=2022-01-01;months=1;first=0;columns=3;highlight=1970-01-01;year";
+        let copy = Calendar::from_config_string(config).unwrap();
+        assert_eq!(copy.dimensions().1, copy.to_string().lines().count());
+    }
+
+    #[test]
+    fn calendar_for_date() {
+        let cal = Calendar::for_date(NaiveDate::from_ymd_opt(2022, 11, 11).unwrap());
+        let out = cal.to_string();
+        assert!(out.starts_with("    November 2022    "));
+        assert_eq!(out.matches("\x1b[7m").count(), 1);
+        assert!(out.contains("\x1b[7m11\x1b[0m"));
+    }
+
+    #[test]
+    fn highlight_far_future() {
+        let cal = Calendar::new((2030, 6, 15), 1, false, false, 0, None, (2030, 6, 15))
+            .unwrap()
+            .with_today((2022, 11, 11))
+            .unwrap();
+        let out = cal.to_string();
+        assert!(out.starts_with("      June 2030      "));
+        let marked = out.lines().filter(|l| l.contains("\x1b[7m")).collect_vec();
+        assert_eq!(marked.len(), 1);
+        assert!(marked[0].contains("\x1b[7m\x1b[31m15\x1b[0m"));
+    }
+
+    #[test]
+    fn highlight_five_digit_year() {
+        let cal = Calendar::new((10000, 1, 1), 12, false, true, 0, Some(3), (10000, 1, 1)).unwrap();
+        let out = strip_color(&cal.to_string());
+        assert_eq!(out.lines().next().unwrap().trim(), "10000");
+        assert!(cal.to_string().contains("\x1b[7m\x1b[31m 1\x1b[0m"));
+    }
+
+    #[test]
+    fn draw_flipped_month() {
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_flip(true);
+        assert_eq!(
+            strip_color(&cal.to_string()),
+            "\
+\x20   November 2022    \n\
+   Su Mo Tu We Th Fr Sa \n\
+   27 28 29 30  1  2  3 \n\
+   20 21 22 23 24 25 26 \n\
+   13 14 15 16 17 18 19 \n\
+\x206  7  8  9 10 11 12 \n\
+   30 31  1  2  3  4  5 \n\
+\x204  5  6  7  8  9 10 "
+        );
+    }
+
+    #[test]
+    fn draw_crlf() {
+        let cal = Calendar::new((2022, 1, 1), 12, false, true, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_line_ending(LineEnding::Crlf);
+        let out = cal.to_string();
+        assert_eq!(out.matches('\n').count(), 1 + 4 * MONTH_ROWS);
+        assert_eq!(out.matches("\r\n").count(), out.matches('\n').count());
+        let lf = Calendar::new((2022, 1, 1), 12, false, true, 0, Some(3), (1970, 1, 1)).unwrap();
+        assert_eq!(out.replace("\r\n", "\n"), lf.to_string());
+
+        let crlf = cal.to_iso_week_list();
+        assert!(crlf.starts_with("2021-W52-6\r\n2021-W52-7\r\n2022-W01-1"));
+        assert_eq!(crlf.replace("\r\n", "\n"), lf.to_iso_week_list());
+        let crlf = cal.to_moon_list();
+        assert_eq!(crlf.matches("\r\n").count(), 364);
+        assert_eq!(crlf.replace("\r\n", "\n"), lf.to_moon_list());
+        let crlf = cal.to_planner(PlannerStyle::default());
+        assert!(crlf.contains("┘\r\n\r\n"));
+        assert_eq!(
+            crlf.replace("\r\n", "\n"),
+            lf.to_planner(PlannerStyle::default())
+        );
+    }
+
+    #[test]
+    fn trailing_newline() {
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, Some(1), (1970, 1, 1)).unwrap();
+        let open = cal.to_string();
+        assert!(!open.ends_with('\n'));
+        let cal = cal.with_trailing_newline(true);
+        assert_eq!(cal.to_string(), open.clone() + "\n");
+        let cal = cal.with_vflip(true).with_line_ending(LineEnding::Crlf);
+        let out = cal.to_string();
+        assert!(out.ends_with(" \r\n"));
+        assert!(!out.ends_with("\r\n\r\n"));
+        let cal = Calendar::from_config_string(&cal.to_config_string()).unwrap();
+        assert_eq!(cal.to_string(), out);
+    }
+
+    #[test]
+    fn min_height_padding() {
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, Some(1), (1970, 1, 1))
+            .unwrap()
+            .with_min_height(10);
+        let out = strip_color(&cal.to_string());
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines.len(), 10);
+        assert_eq!(cal.dimensions(), (21, 10));
+        assert_eq!(lines[7], " 4  5  6  7  8  9 10 ");
+        assert_eq!(lines[8..], [" ".repeat(21), " ".repeat(21)]);
+        // already tall enough
+        let cal = cal.with_min_height(4);
+        assert_eq!(strip_color(&cal.to_string()).lines().count(), 8);
+        let cal = cal.with_trim(true).with_min_height(7);
+        assert_eq!(strip_color(&cal.to_string()).lines().count(), 7);
+    }
+
+    #[test]
+    fn trim_single_month() {
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_trim(true);
+        let out = strip_color(&cal.to_string());
+        assert_eq!(out.lines().count(), 7);
+        assert!(out.ends_with("27 28 29 30  1  2  3 "));
+        let cal = Calendar::new((2022, 11, 1), 2, false, false, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_trim(true);
+        assert_eq!(cal.to_string().lines().count(), 8);
+    }
+
+    #[test]
+    fn today_prefix() {
+        let cal = Calendar::new((2022, 11, 1), 1, false, false, 0, Some(3), (2022, 11, 11))
+            .unwrap()
+            .with_today_prefix(Some('>'));
+        let out = cal.to_string();
+        assert!(!out.contains("\x1b[7m"));
+        assert_eq!(
+            strip_color(&out),
+            "\
+\x20   November 2022    \n\
+\x20Su Mo Tu We Th Fr Sa\n\
+\x2030 31  1  2  3  4  5\n\
+\x20 6  7  8  9 10>11 12\n\
+\x2013 14 15 16 17 18 19\n\
+\x2020 21 22 23 24 25 26\n\
+\x2027 28 29 30  1  2  3\n\
+\x20 4  5  6  7  8  9 10"
+        );
+        let divided = strip_color(&cal.with_week_dividers(true).to_string());
+        let lines: Vec<_> = divided.lines().collect();
+        assert_eq!(lines[2], " 30 31  1  2  3  4  5");
+        assert_eq!(lines[3], format!(" {}", "─".repeat(20)));
+        assert_eq!(lines[4], "  6  7  8  9 10>11 12");
+    }
+
+    #[test]
+    fn week_epoch_column() {
+        let cal = Calendar::new((2022, 11, 1), 2, false, false, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_week_epoch((2022, 9, 1))
+            .unwrap();
+        assert_eq!(
+            strip_color(&cal.to_string()),
+            "\
+\x20      November 2022            December 2022    \n\
+\x20  Su Mo Tu We Th Fr Sa     Su Mo Tu We Th Fr Sa \n\
+   10        1  2  3  4  5  14              1  2  3 \n\
+   11  6  7  8  9 10 11 12  15  4  5  6  7  8  9 10 \n\
+   12 13 14 15 16 17 18 19  16 11 12 13 14 15 16 17 \n\
+   13 20 21 22 23 24 25 26  17 18 19 20 21 22 23 24 \n\
+   14 27 28 29 30           18 25 26 27 28 29 30 31 \n\
+\x20                                                "
+        );
+    }
+
+    #[test]
+    fn week_epoch_three_digits() {
+        let cal = Calendar::new((2022, 11, 1), 2, false, false, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_week_epoch((2020, 1, 1))
+            .unwrap();
+        let out = strip_color(&cal.to_string());
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(
+            lines[1],
+            "    Su Mo Tu We Th Fr Sa      Su Mo Tu We Th Fr Sa "
+        );
+        assert_eq!(
+            lines[2],
+            "149        1  2  3  4  5  153              1  2  3 "
+        );
+        assert!(lines.iter().all(|l| l.len() == cal.dimensions().0));
+    }
+
     #[test]
     fn draw_two_months() {
         let cal = Calendar::new((2022, 11, 1), 2, false, false, 0, Some(3), (1970, 1, 1)).unwrap();
@@ -289,6 +3860,138 @@ mod tests {
         );
     }
 
+    #[test]
+    fn year_with_month_highlight() {
+        let cal = Calendar::new((2022, 3, 1), 12, false, true, 0, Some(3), (2022, 3, 1)).unwrap();
+        let out = cal.to_string();
+        let stripped = strip_color(&out);
+        for m in 1..=12 {
+            let name = Month::from_u32(m).unwrap().name();
+            assert!(stripped.contains(name), "missing {}", name);
+        }
+        assert_eq!(out.matches("\x1b[7m").count(), 1);
+        let first_week = out.lines().nth(4).unwrap();
+        assert!(first_week.ends_with("      \x1b[7m 1\x1b[0m  2  3  4 \x1b[31m 5\x1b[0m "));
+    }
+
+    #[test]
+    fn draw_year_four_columns() {
+        let cal = Calendar::new((2022, 1, 1), 12, false, true, 0, Some(4), (1970, 1, 1)).unwrap();
+        let out = strip_color(&cal.to_string());
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines.len(), 2 + 3 * MONTH_ROWS);
+        let width = 4 * MONTH_WIDTH + 3 * 2;
+        assert_eq!(lines[0], format!("{:^1$}", 2022, width));
+        assert!(lines[2].contains("January") && lines[2].contains("April"));
+        assert!(lines[2 + MONTH_ROWS].contains("May"));
+        assert!(lines[2 + 2 * MONTH_ROWS].contains("December"));
+    }
+
+    #[test]
+    fn draw_year_stacked() {
+        let cal = Calendar::new((2022, 1, 1), 12, false, true, 0, Some(1), (1970, 1, 1)).unwrap();
+        let out = strip_color(&cal.to_string());
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines.len(), 2 + 12 * MONTH_ROWS);
+        assert!(lines[2..].iter().all(|l| l.len() == MONTH_WIDTH));
+        for m in 1..=12 {
+            let name = Month::from_u32(m).unwrap().name();
+            assert_eq!(lines[2 + (m as usize - 1) * MONTH_ROWS].trim(), name);
+        }
+    }
+
+    #[test]
+    fn center_last_row() {
+        let cal = Calendar::new((2022, 1, 1), 14, false, false, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_last_row_align(Align::Center);
+        let out = strip_color(&cal.to_string());
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines.len(), 5 * MONTH_ROWS);
+        let last = lines[4 * MONTH_ROWS];
+        assert_eq!(
+            last,
+            format!(
+                "{}{:^21} {:^21}",
+                " ".repeat(11),
+                "January 2023",
+                "February 2023"
+            )
+        );
+        let cal = cal.with_last_row_align(Align::Right);
+        let out = strip_color(&cal.to_string());
+        assert!(out
+            .lines()
+            .nth(4 * MONTH_ROWS)
+            .unwrap()
+            .starts_with(&" ".repeat(22)));
+    }
+
+    #[test]
+    fn mark_current_month() {
+        let cal = Calendar::new((2022, 1, 1), 12, false, true, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_today((2022, 11, 11))
+            .unwrap()
+            .with_mark_current_month(true);
+        let out = cal.to_string();
+        let lines: Vec<_> = out.lines().collect();
+        let november = 2 + 3 * MONTH_ROWS..2 + 4 * MONTH_ROWS;
+        for (i, line) in lines.iter().enumerate() {
+            assert_eq!(line.contains("\x1b[44m"), november.contains(&i));
+        }
+        let header = lines[november.start];
+        let before = &header[..header.find("\x1b[44m").unwrap()];
+        let october = NaiveDate::from_ymd_opt(2022, 10, 1).unwrap();
+        assert_eq!(
+            before,
+            month_year_line(october, true, &Style::default()) + "  "
+        );
+        let nov_header = month_year_line(october + Months::new(1), true, &Style::default());
+        assert!(header.contains(&format!("\x1b[44m{}\x1b[0m  ", nov_header)));
+    }
+
+    #[test]
+    fn years_out_of_range() {
+        let cal =
+            || Calendar::new((2020, 1, 1), 12, false, true, 0, Some(3), (1970, 1, 1)).unwrap();
+        assert!(cal().with_years(300000 - 2020).is_none());
+        assert!(cal().with_years(u32::MAX).is_none());
+        assert!(cal().with_years(i32::MAX as u32).is_none());
+        assert!(cal().with_years(3).is_some());
+        // up to the last year chrono can represent
+        let last = Calendar::new((262140, 1, 1), 12, false, true, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_years(4)
+            .unwrap()
+            .with_week_epoch((262140, 1, 1))
+            .unwrap();
+        assert!(strip_color(&last.to_string()).contains("262143"));
+        assert!(last.to_planner(PlannerStyle::default()).ends_with('┘'));
+    }
+
+    #[test]
+    fn draw_two_years() {
+        let cal = Calendar::new((2020, 1, 1), 12, false, true, 0, Some(3), (1970, 1, 1))
+            .unwrap()
+            .with_years(2)
+            .unwrap();
+        let out = strip_color(&cal.to_string());
+        let lines: Vec<_> = out.lines().collect();
+        let year_lines = 2 + 4 * MONTH_ROWS;
+        assert_eq!(lines.len(), 2 * year_lines + 1);
+        assert_eq!(lines[0].trim(), "2020");
+        assert_eq!(lines[year_lines], "");
+        assert_eq!(lines[year_lines + 1].trim(), "2021");
+        assert!(lines[year_lines + 3].contains("January"));
+        let single =
+            Calendar::new((2021, 1, 1), 12, false, true, 0, Some(3), (1970, 1, 1)).unwrap();
+        assert_eq!(
+            lines[year_lines + 1..].join("\n"),
+            strip_color(&single.to_string())
+        );
+    }
+
     #[test]
     fn draw_year() {
         let cal = Calendar::new((2022, 1, 1), 12, false, true, 0, Some(3), (1970, 1, 1)).unwrap();
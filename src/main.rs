@@ -1,6 +1,10 @@
-use carender::Calendar;
+use carender::{
+    date_info, iso_week_view, locale_first_day, locale_weekday_names, parse_date,
+    parse_day_effects, read_date, term_width, weeks_view, year_progress, Align, Calendar, DayAlign,
+    Effect, LineEnding, PlannerStyle, RangeHighlightStyle, WeekdayBase, WeekdayCase, Year,
+};
 
-use chrono::{Datelike, Local};
+use chrono::{Datelike, Local, NaiveDate};
 use clap::Parser;
 
 #[derive(Parser, Debug)]
@@ -22,6 +26,25 @@ struct Cli {
     #[arg(group = "nmon", short = 'n', long = "months", value_name = "NUM")]
     nmon_n: Option<u32>,
 
+    /// Show whole years from FIRST to LAST, e.g. 2020..2022
+    #[arg(group = "nmon", long, value_name = "FIRST..LAST", value_parser = parse_years)]
+    years: Option<(i32, i32)>,
+
+    /// Show NUM weeks starting with the date's week, at most 10000
+    #[arg(group = "nmon", long, value_name = "NUM",
+          value_parser = clap::value_parser!(u32).range(..=10000))]
+    weeks: Option<u32>,
+
+    /// Show the academic year holding the date, starting in MONTH
+    #[arg(group = "nmon", long, value_name = "MONTH", num_args = 0..=1,
+          default_missing_value = "9",
+          value_parser = clap::value_parser!(u32).range(1..=12))]
+    academic: Option<u32>,
+
+    /// Show ISO week NUM of the year, Monday to Sunday
+    #[arg(group = "nmon", long, value_name = "NUM")]
+    iso_week: Option<u32>,
+
     /// Span the date when displaying multiple months
     #[arg(short = 'S', long, requires = "nmon_n")]
     span: bool,
@@ -34,16 +57,211 @@ struct Cli {
     #[arg(group = "fday", short = 'm', long = "monday")]
     fday_m: bool,
 
-    /// Set first day of week (Sunday = 0, Monday = 1, ...)
-    #[arg(group = "fday", short = 'f', long = "first", value_name = "0-6")]
-    fday_n: Option<u8>,
+    /// Set first day of week (Sunday = 0, Monday = 1, ..., or auto for locale)
+    #[arg(group = "fday", short = 'f', long = "first", value_name = "0-7|auto",
+          value_parser = parse_first)]
+    fday_n: Option<First>,
+
+    /// Number weekdays for --first from sunday-0, iso-monday-0 or iso-monday-1
+    #[arg(long, value_name = "BASE", default_value = "sunday-0", value_parser = parse_base)]
+    weekday_base: WeekdayBase,
 
     /// Format calendar into NUM columns of months
     #[arg(short = 'c', long = "column", value_name = "NUM")]
     ncol: Option<usize>,
 
-    /// Defaults to current year
-    year: Option<i32>,
+    /// Stack months in a single column, whatever the terminal width
+    #[arg(long, conflicts_with_all = ["ncol", "layout"])]
+    stack: bool,
+
+    /// Lay out the year as COLSxROWS months, e.g. 4x3
+    #[arg(long, value_name = "COLSxROWS", conflicts_with = "ncol", value_parser = parse_layout)]
+    layout: Option<usize>,
+
+    /// Separate side-by-side months with CHAR, like '│'
+    #[arg(long, value_name = "CHAR")]
+    separator: Option<char>,
+
+    /// Place a last row of fewer months: left, center or right
+    #[arg(long, value_name = "ALIGN", value_parser = parse_align)]
+    last_row: Option<Align>,
+
+    /// Effect of days of the previous month, like dim or italic
+    #[arg(long, value_name = "EFFECT", default_value = "dim", value_parser = parse_effect)]
+    prev_days: Effect,
+
+    /// Effect of days of the next month, like dim or italic
+    #[arg(long, value_name = "EFFECT", default_value = "dim", value_parser = parse_effect)]
+    next_days: Effect,
+
+    /// List the weeks of each month bottom-up
+    #[arg(long)]
+    flip: bool,
+
+    /// Reverse all lines, for printing on transparent film
+    #[arg(long)]
+    vflip: bool,
+
+    /// Write days as dots, except today
+    #[arg(long)]
+    minimal: bool,
+
+    /// Write with full-width characters, doubling every column
+    #[arg(long)]
+    full_width: bool,
+
+    /// Leave out days of the adjacent months
+    #[arg(long)]
+    no_adjacent: bool,
+
+    /// Shade every other week row
+    #[arg(long)]
+    zebra_weeks: bool,
+
+    /// Leave NUM blank lines below the weekday row
+    #[arg(long, value_name = "NUM", default_value_t = 0)]
+    header_gap: usize,
+
+    /// Name today's date above the calendar
+    #[arg(long)]
+    today_header: bool,
+
+    /// Write the year of year views in block digits
+    #[arg(long)]
+    block_year: bool,
+
+    /// Center TEXT above the calendar
+    #[arg(long, value_name = "TEXT")]
+    title: Option<String>,
+
+    /// Highlight the days from FIRST to LAST, endpoints apart
+    #[arg(long, value_name = "FIRST..LAST", value_parser = parse_date_range)]
+    highlight_range: Option<DateRange>,
+
+    /// Effect of the highlighted range's endpoints
+    #[arg(long, value_name = "EFFECT", default_value = "bold", value_parser = parse_effect)]
+    range_ends: Effect,
+
+    /// Effect of the days inside the highlighted range
+    #[arg(long, value_name = "EFFECT", default_value = "reverse", value_parser = parse_effect)]
+    range_inside: Effect,
+
+    /// Highlight the days listed in FILE, one 'YYYY-MM-DD[,EFFECT]' a line
+    #[arg(long, value_name = "FILE")]
+    highlight_file: Option<std::path::PathBuf>,
+
+    /// Draw lines between weeks
+    #[arg(long)]
+    week_dividers: bool,
+
+    /// Keep day numbers to the left or right of cells
+    #[arg(long, value_name = "SIDE", default_value = "right", value_parser = parse_day_align)]
+    day_align: DayAlign,
+
+    /// Write day numbers in base NUM (6-36)
+    #[arg(long, value_name = "NUM", default_value_t = 10,
+          value_parser = clap::value_parser!(u32).range(6..=36))]
+    radix: u32,
+
+    /// Drop trailing week rows outside a single month
+    #[arg(long)]
+    trim: bool,
+
+    /// Pad a single month with blank lines to at least NUM lines
+    #[arg(long, value_name = "NUM", default_value_t = 0)]
+    min_height: usize,
+
+    /// Show the month number in month headers
+    #[arg(long)]
+    month_number: bool,
+
+    /// Mark today with CHAR instead of reverse video
+    #[arg(long, value_name = "CHAR")]
+    today_prefix: Option<char>,
+
+    /// Tint the block of the current month
+    #[arg(long)]
+    mark_month: bool,
+
+    /// Do not paint weekends red
+    #[arg(long)]
+    no_weekend_color: bool,
+
+    /// Number weeks from the week of DATE in a leading column
+    #[arg(long, value_name = "YYYY-MM-DD", value_parser = parse_ymd)]
+    week_epoch: Option<(i32, u32, u32)>,
+
+    /// Write weekdays in title, upper or lower case
+    #[arg(long, value_name = "CASE", default_value = "title", value_parser = parse_case)]
+    weekday_case: WeekdayCase,
+
+    /// Abbreviate weekdays as the locale does
+    #[arg(long)]
+    locale_weekdays: bool,
+
+    /// Draw days as empty checkboxes
+    #[arg(long)]
+    checkbox: bool,
+
+    /// Cut lines wider than the terminal, marking them with '…'
+    #[arg(long)]
+    truncate: bool,
+
+    /// End lines with CRLF instead of LF
+    #[arg(long)]
+    crlf: bool,
+
+    /// Exit with failure if DATE is not a valid date
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    check: Option<String>,
+
+    /// Count the days from today to DATE instead of showing a calendar
+    #[arg(long, value_name = "YYYY-MM-DD", value_parser = parse_ymd)]
+    countdown: Option<(i32, u32, u32)>,
+
+    /// Print how much of the year has passed by the date
+    #[arg(long)]
+    progress: bool,
+
+    /// Print a line about today instead of a calendar
+    #[arg(long)]
+    today_line: bool,
+
+    /// Describe the highlighted day below the calendar
+    #[arg(long)]
+    info: bool,
+
+    /// Exit with the number of displayed days as status, at most 255
+    #[arg(long)]
+    exit_day_count: bool,
+
+    /// Write each month to its own file named like 'cal-{year}-{month:02}.txt'
+    #[arg(long, value_name = "PATTERN")]
+    split_output: Option<String>,
+
+    /// List each day as an ISO week date instead of a calendar
+    #[arg(long)]
+    iso_list: bool,
+
+    /// Print blank boxes to write in, numbered in their corners
+    #[arg(long, conflicts_with_all = ["iso_list", "csv"])]
+    planner: bool,
+
+    /// List how much of the moon is lit each day instead of a calendar
+    #[arg(long, conflicts_with_all = ["iso_list", "csv", "planner"])]
+    moon_percent: bool,
+
+    /// Print one CSV row per day instead of a calendar
+    #[arg(long, conflicts_with = "iso_list")]
+    csv: bool,
+
+    /// Add ISO week numbers and days of the year to CSV rows
+    #[arg(long, requires = "csv")]
+    export_numbers: bool,
+
+    /// Defaults to current year, or '-' to read a date from stdin
+    #[arg(value_parser = parse_year_arg)]
+    year: Option<YearArg>,
 
     /// Defaults to current month
     month: Option<u32>,
@@ -52,11 +270,179 @@ struct Cli {
     day: Option<u32>,
 }
 
+#[derive(Debug, Clone, Copy)]
+enum YearArg {
+    Year(i32),
+
+    /// read a date like "2022-11" from stdin
+    Stdin,
+}
+
+fn parse_year_arg(s: &str) -> Result<YearArg, String> {
+    if s == "-" {
+        return Ok(YearArg::Stdin);
+    }
+    s.parse()
+        .map(YearArg::Year)
+        .map_err(|_| format!("invalid year `{}`", s))
+}
+
+#[derive(Debug, Clone, Copy)]
+enum First {
+    Day(u8),
+
+    /// decided by the locale
+    Auto,
+}
+
+fn parse_first(s: &str) -> Result<First, String> {
+    if s == "auto" {
+        return Ok(First::Auto);
+    }
+    match s.parse() {
+        Ok(n) if n <= 7 => Ok(First::Day(n)),
+        _ => Err(format!("expected 0-7 or auto, got `{}`", s)),
+    }
+}
+
+fn parse_base(s: &str) -> Result<WeekdayBase, String> {
+    match s {
+        "sunday-0" => Ok(WeekdayBase::Sunday0),
+        "iso-monday-0" => Ok(WeekdayBase::Monday0),
+        "iso-monday-1" => Ok(WeekdayBase::Monday1),
+        _ => Err(format!(
+            "expected sunday-0, iso-monday-0 or iso-monday-1, got `{}`",
+            s
+        )),
+    }
+}
+
+/// The locale for dates, as POSIX resolves it.
+fn time_locale() -> String {
+    ["LC_ALL", "LC_TIME", "LANG"]
+        .iter()
+        .find_map(|key| std::env::var(key).ok().filter(|v| !v.is_empty()))
+        .unwrap_or_default()
+}
+
+fn parse_ymd(s: &str) -> Result<(i32, u32, u32), String> {
+    parse_date(s).ok_or_else(|| format!("invalid date `{}`", s))
+}
+
+fn parse_effect(s: &str) -> Result<Effect, String> {
+    s.parse()
+}
+
+fn parse_case(s: &str) -> Result<WeekdayCase, String> {
+    match s {
+        "title" => Ok(WeekdayCase::Title),
+        "upper" => Ok(WeekdayCase::Upper),
+        "lower" => Ok(WeekdayCase::Lower),
+        _ => Err(format!("expected title, upper or lower, got `{}`", s)),
+    }
+}
+
+fn parse_day_align(s: &str) -> Result<DayAlign, String> {
+    match s {
+        "left" => Ok(DayAlign::Left),
+        "right" => Ok(DayAlign::Right),
+        _ => Err(format!("expected left or right, got `{}`", s)),
+    }
+}
+
+fn parse_align(s: &str) -> Result<Align, String> {
+    match s {
+        "left" => Ok(Align::Left),
+        "center" => Ok(Align::Center),
+        "right" => Ok(Align::Right),
+        _ => Err(format!("expected left, center or right, got `{}`", s)),
+    }
+}
+
+/// First and last days, inclusive.
+type DateRange = ((i32, u32, u32), (i32, u32, u32));
+
+/// Parse an inclusive range of dates like "2022-11-10..2022-12-03".
+fn parse_date_range(s: &str) -> Result<DateRange, String> {
+    let (first, last) = s
+        .split_once("..")
+        .ok_or_else(|| format!("expected FIRST..LAST, got `{}`", s))?;
+    let (first, last) = (parse_ymd(first)?, parse_ymd(last)?);
+    if first > last {
+        return Err(format!("`{}` ends before it starts", s));
+    }
+    Ok((first, last))
+}
+
+/// Parse an inclusive range of years like "2020..2022".
+fn parse_years(s: &str) -> Result<(i32, i32), String> {
+    let (first, last) = s
+        .split_once("..")
+        .ok_or_else(|| format!("expected FIRST..LAST, got `{}`", s))?;
+    let year = |s: &str| {
+        s.parse()
+            .ok()
+            .and_then(Year::new)
+            .map(|y| y.year())
+            .ok_or_else(|| format!("invalid year `{}`", s))
+    };
+    let (first, last) = (year(first)?, year(last)?);
+    if first > last {
+        return Err(format!("{} comes after {}", first, last));
+    }
+    Ok((first, last))
+}
+
+/// Parse a year layout like "4x3" into its number of columns.
+fn parse_layout(s: &str) -> Result<usize, String> {
+    let (cols, rows) = s
+        .split_once('x')
+        .ok_or_else(|| format!("expected COLSxROWS, got `{}`", s))?;
+    let cols: usize = cols
+        .parse()
+        .map_err(|_| format!("invalid columns `{}`", cols))?;
+    let rows: usize = rows
+        .parse()
+        .map_err(|_| format!("invalid rows `{}`", rows))?;
+    if cols.checked_mul(rows) != Some(12) {
+        return Err(format!("{}x{} does not hold 12 months", cols, rows));
+    }
+    Ok(cols)
+}
+
 fn main() {
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
+
+    let year = match cli.year {
+        Some(YearArg::Year(year)) => Some(year),
+        Some(YearArg::Stdin) => match read_date(std::io::stdin().lock()) {
+            Some((year, month, day)) => {
+                cli.month = cli.month.or(month);
+                cli.day = cli.day.or(day);
+                Some(year)
+            }
+            None => {
+                eprintln!("invalid date on stdin");
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    if let Some(date) = cli.check {
+        if parse_date(&date).is_none() {
+            eprintln!("invalid date: {}", date);
+            std::process::exit(1);
+        }
+        return;
+    }
 
     let now = Local::now();
-    let y = cli.year.unwrap_or_else(|| now.year());
+    let y = cli
+        .years
+        .map(|(first, _)| first)
+        .or(year)
+        .unwrap_or_else(|| now.year());
     let m = cli.month.unwrap_or_else(|| now.month());
     let d = cli.day.unwrap_or(1);
 
@@ -64,11 +450,12 @@ fn main() {
         (1, false, false)
     } else if cli.nmon_3 {
         (3, true, false)
-    } else if cli.nmon_y {
+    } else if cli.nmon_y || cli.years.is_some() {
+        // a month argument does not narrow `-y`, it only moves the highlight
         (12, false, true)
     } else if let Some(n) = cli.nmon_n {
         (n.max(1), cli.span, false)
-    } else if cli.year.is_some() && cli.month.is_none() {
+    } else if year.is_some() && cli.month.is_none() {
         // special case: `cal YEAR` should print whole year calendar
         (12, false, true)
     } else {
@@ -77,19 +464,198 @@ fn main() {
 
     let fday = match (cli.fday_s, cli.fday_m, cli.fday_n) {
         (_, true, _) => 1,
-        (_, _, Some(n)) => n,
+        (_, _, Some(First::Day(n))) => match cli.weekday_base.first_day(n) {
+            Some(fday) => fday,
+            None => {
+                eprintln!("invalid first day of week: {}", n);
+                std::process::exit(1);
+            }
+        },
+        (_, _, Some(First::Auto)) => locale_first_day(&time_locale()),
         _ => 0,
     };
 
-    let ncol = cli.ncol;
+    let ncol = if cli.stack {
+        Some(1)
+    } else {
+        cli.ncol.or(cli.layout)
+    };
 
+    // an explicit day is highlighted wherever it is, otherwise today is
+    // (and shows only if it falls in the displayed months)
     let hlight = if cli.day.is_some() {
         (y, m, d)
     } else {
         (now.year(), now.month(), now.day())
     };
+    if NaiveDate::from_ymd_opt(y, m, d).is_none() {
+        eprintln!("invalid date: {}-{}-{}", y, m, d);
+        std::process::exit(1);
+    }
+
+    let line_ending = if cli.crlf {
+        LineEnding::Crlf
+    } else {
+        LineEnding::Lf
+    };
+
+    if cli.progress {
+        let progress = year_progress(hlight).unwrap();
+        print!(
+            "{:.1}% of {}{}",
+            progress * 100.0,
+            hlight.0,
+            line_ending.as_str()
+        );
+        return;
+    }
+
+    if let Some(week) = cli.iso_week {
+        match iso_week_view(y, week, line_ending) {
+            Some(view) => print!("{}{}", view, line_ending.as_str()),
+            None => {
+                eprintln!("{} has no ISO week {}", y, week);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(count) = cli.weeks {
+        match weeks_view(hlight, fday, count, line_ending) {
+            Some(view) => print!("{}{}", view, line_ending.as_str()),
+            None => {
+                eprintln!("{} weeks from {}-{}-{} run out of range", count, y, m, d);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let cal = Calendar::new((y, m, d), nmon, span, year, fday, ncol, hlight)
+        .unwrap()
+        .with_flip(cli.flip)
+        .with_vflip(cli.vflip)
+        .with_minimal(cli.minimal)
+        .with_full_width(cli.full_width)
+        .with_adjacent_days(!cli.no_adjacent)
+        .with_export_numbers(cli.export_numbers)
+        .with_month_separator(cli.separator.unwrap_or(' '))
+        .with_zebra_weeks(cli.zebra_weeks)
+        .with_week_dividers(cli.week_dividers)
+        .with_overflow_effects(cli.prev_days, cli.next_days)
+        .with_weekday_case(cli.weekday_case)
+        .with_day_align(cli.day_align)
+        .with_header_gap(cli.header_gap)
+        .with_radix(cli.radix)
+        .unwrap()
+        .with_trim(cli.trim)
+        .with_min_height(cli.min_height)
+        .with_month_number(cli.month_number)
+        .with_today_prefix(cli.today_prefix)
+        .with_mark_current_month(cli.mark_month)
+        .with_weekend_color(!cli.no_weekend_color)
+        .with_checkbox(cli.checkbox)
+        .with_max_width(cli.truncate.then(term_width))
+        .with_title(cli.title)
+        .with_today_header(cli.today_header)
+        .with_block_banner(cli.block_year)
+        .with_last_row_align(cli.last_row.unwrap_or_default())
+        .with_line_ending(line_ending)
+        .with_trailing_newline(true);
 
-    let cal = Calendar::new((y, m, d), nmon, span, year, fday, ncol, hlight).unwrap();
+    let cal = match locale_weekday_names(&time_locale()) {
+        Some(names) if cli.locale_weekdays => cal.with_weekday_names(names),
+        _ => cal,
+    };
+
+    let cal = match cli.highlight_range {
+        Some((first, last)) => {
+            let style = RangeHighlightStyle {
+                endpoints: cli.range_ends,
+                interior: cli.range_inside,
+            };
+            cal.with_highlight_range(first, last, style).unwrap()
+        }
+        None => cal,
+    };
+
+    let cal = match &cli.highlight_file {
+        Some(path) => {
+            let effects = std::fs::read_to_string(path)
+                .map_err(|err| err.to_string())
+                .and_then(|text| parse_day_effects(&text));
+            match effects {
+                Ok(effects) => cal.with_day_effects(&effects).unwrap(),
+                Err(err) => {
+                    eprintln!("{}: {}", path.display(), err);
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => cal,
+    };
+
+    let cal = match cli.week_epoch {
+        Some(epoch) => cal.with_week_epoch(epoch).unwrap(),
+        None => cal,
+    };
+
+    let cal = match cli.academic {
+        Some(start) => cal.with_academic_year(start).unwrap(),
+        None => cal,
+    };
+
+    let cal = match cli.years {
+        Some((first, last)) => cal.with_years((last - first + 1) as u32).unwrap(),
+        None => cal,
+    };
+
+    if let Some(pattern) = cli.split_output {
+        // files are not terminals
+        colored::control::set_override(false);
+        let mut failed = false;
+        for (path, result) in cal.write_split(&pattern) {
+            if let Err(err) = result {
+                eprintln!("cannot write {}: {}", path, err);
+                failed = true;
+            }
+        }
+        if failed {
+            std::process::exit(1);
+        }
+    } else if cli.today_line {
+        print!("{}{}", cal.today_line(), line_ending.as_str());
+    } else if let Some(target) = cli.countdown {
+        let days = cal.days_until(target).unwrap();
+        let (y, m, d) = target;
+        let unit = if days.abs() == 1 { "day" } else { "days" };
+        let eol = line_ending.as_str();
+        if days < 0 {
+            print!("{} {} since {:04}-{:02}-{:02}{}", -days, unit, y, m, d, eol);
+        } else {
+            print!("{} {} until {:04}-{:02}-{:02}{}", days, unit, y, m, d, eol);
+        }
+    } else if cli.iso_list {
+        print!("{}{}", cal.to_iso_week_list(), line_ending.as_str());
+    } else if cli.moon_percent {
+        print!("{}{}", cal.to_moon_list(), line_ending.as_str());
+    } else if cli.planner {
+        print!(
+            "{}{}",
+            cal.to_planner(PlannerStyle::default()),
+            line_ending.as_str()
+        );
+    } else if cli.csv {
+        print!("{}", cal.to_csv());
+    } else {
+        print!("{}", cal);
+        if cli.info {
+            print!("{}{}", date_info(hlight).unwrap(), line_ending.as_str());
+        }
+    }
 
-    println!("{}", cal);
+    if cli.exit_day_count {
+        std::process::exit(cal.day_count().min(255) as i32);
+    }
 }